@@ -0,0 +1,370 @@
+use std::{collections::HashMap, fs::File, sync::Arc};
+
+use crate::{buffers, meta, ColumnData, ColumnType, CsvDialect, IndexColumn, LladError, Metadata};
+
+/// Columns of logged data in a fixed order, as read back by [`read_csv_as_audio_data`] or kept by a
+/// [`crate::SampleLogger`]. The order of the columns is the order of the columns in the CSV, so iterating an
+/// `AudioData` and writing it out again results in the same layout. Every column keeps its values in their own type,
+/// see [`ColumnData`].
+///
+/// # Fields
+///
+/// * `keys`: The key (CSV header) of every column.
+/// * `columns`: The values of every column, at the same index as its key in `keys`.
+/// * `metadata`: What is known about the session, such as the sample rate.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioData {
+    pub(crate) keys: Vec<String>,
+    pub(crate) columns: Vec<ColumnData>,
+    pub(crate) metadata: Metadata,
+}
+
+impl AudioData {
+    /// Creates an `AudioData` without any columns.
+    ///
+    /// # Returns
+    ///
+    /// * `AudioData`: The newly created, empty `AudioData`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a column after all existing columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The key of the new column.
+    /// * `values`: The values of the new column.
+    pub fn push_column(&mut self, key: String, values: ColumnData) {
+        self.keys.push(key);
+        self.columns.push(values);
+    }
+
+    /// Returns the values of a column by its key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The key of the column.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&ColumnData>`: The values of the column, or `None` if no column has this key. Use e.g.
+    ///   [`ColumnData::as_f32`] to get the values in their type.
+    pub fn get(&self, key: &str) -> Option<&ColumnData> {
+        self.position(key).map(|i| &self.columns[i])
+    }
+
+    /// Returns the index of a column by its key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The key of the column.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<usize>`: The index of the column in the order of this `AudioData`, or `None` if no column has
+    ///   this key.
+    pub fn position(&self, key: &str) -> Option<usize> {
+        self.keys.iter().position(|k| k == key)
+    }
+
+    /// Returns the keys of all columns, in order.
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Iterates over all columns in order, yielding the key and values of every column.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ColumnData)> {
+        self.keys
+            .iter()
+            .map(String::as_str)
+            .zip(self.columns.iter())
+    }
+
+    /// Returns the columns of a multichannel probe written by [`crate::SampleLogger::register_probe`], which are the
+    /// columns named `probe[0]`, `probe[1]` and so on.
+    ///
+    /// # Arguments
+    ///
+    /// * `probe`: The key of the probe, without a channel.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&ColumnData>`: The column of every channel, in channel order. Stops at the first channel that has no
+    ///   column, and is empty if there is no probe with this key.
+    pub fn channels(&self, probe: &str) -> Vec<&ColumnData> {
+        (0..)
+            .map_while(|channel| self.get(&channel_key(probe, channel)))
+            .collect()
+    }
+
+    /// Returns the keys of all multichannel probes, see [`AudioData::channels`], in the order of their first column.
+    pub fn probes(&self) -> Vec<&str> {
+        let mut probes = Vec::new();
+
+        for key in &self.keys {
+            if let Some((probe, _)) = split_channel_key(key) {
+                if !probes.contains(&probe) {
+                    probes.push(probe);
+                }
+            }
+        }
+
+        probes
+    }
+
+    /// Returns what is known about the session in which the data was logged, such as the sample rate.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns the time of every row in seconds, counted from the first frame the logger saw, so that the columns can
+    /// be plotted against time instead of against the row. For the CSV of a [`crate::Trigger`], the first row is not at
+    /// 0 seconds but at the first frame of its history.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<f64>>`: The time of every row, as many as the longest column has values, or `None` if the sample
+    ///   rate is not known.
+    pub fn time_axis(&self) -> Option<Vec<f64>> {
+        let sample_rate = f64::from(self.metadata.sample_rate?);
+        let rows = self.columns.iter().map(ColumnData::len).max().unwrap_or(0);
+
+        Some(
+            (0..rows)
+                .map(|row| (self.metadata.first_sample + row as u64) as f64 / sample_rate)
+                .collect(),
+        )
+    }
+
+    /// Returns the rows at which a call to `Plugin::process` began, for a CSV of a logger that logged its buffers with
+    /// [`crate::SampleLogger::log_buffers`]. These are the rows where the `buffer.offset` column is 0.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<usize>`: The index of the first row of every buffer, in order. Empty if the buffers were not logged.
+    pub fn buffer_starts(&self) -> Vec<usize> {
+        let offsets = self
+            .get(buffers::OFFSET_KEY)
+            .and_then(ColumnData::as_i64)
+            .unwrap_or_default();
+
+        offsets
+            .iter()
+            .enumerate()
+            .filter(|&(_, &offset)| offset == 0)
+            .map(|(row, _)| row)
+            .collect()
+    }
+
+    /// Returns the number of columns.
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns `true` if there are no columns.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+}
+
+/// Returns the key of the column of a channel of a multichannel probe.
+///
+/// # Arguments
+///
+/// * `probe`: The key of the probe.
+/// * `channel`: The index of the channel.
+///
+/// # Returns
+///
+/// * `String`: The key of the column, `probe[channel]`.
+pub(crate) fn channel_key(probe: &str, channel: usize) -> String {
+    format!("{probe}[{channel}]")
+}
+
+/// Splits the key of the column of a channel into the key of the probe and the channel, the inverse of
+/// [`channel_key`].
+///
+/// # Arguments
+///
+/// * `key`: The key of a column.
+///
+/// # Returns
+///
+/// * `Option<(&str, usize)>`: The key of the probe and the channel, or `None` if the key is not of a channel.
+fn split_channel_key(key: &str) -> Option<(&str, usize)> {
+    let (probe, channel) = key.strip_suffix(']')?.rsplit_once('[')?;
+    Some((probe, channel.parse().ok()?))
+}
+
+/// Reads a CSV file and converts it into an [`AudioData`] where each key corresponds to a column header
+/// and each value is the column of values representing the audio data. This function exists to
+/// allow you to write your own plotters/handlers of the data written to the output of this library,
+/// so in a sense this function is the inverse of the result that [`crate::SampleLogger`] produces.
+///
+/// The columns are kept in the order of the CSV. Empty fields, which [`crate::SampleLogger`] writes at the end of
+/// columns that are shorter than the others, are skipped.
+///
+/// The type of every column is read from the sidecar file that [`crate::SampleLogger`] writes next to the CSV, named
+/// after the CSV with `.meta` appended. Columns that are not in the sidecar file, or all columns of a CSV without one,
+/// are read as [`ColumnType::F32`]. The [`Metadata`] in the sidecar file, such as the sample rate, is available
+/// through [`AudioData::metadata`] and [`AudioData::time_axis`].
+///
+/// # Arguments
+///
+/// * `filename` - A string representing the path to the CSV file.
+///
+/// # Returns
+///
+/// * `Result<AudioData, LladError>` - On success, returns an [`AudioData`] where each
+///   key is a column header from the CSV and each value is the column of values representing the audio
+///   data in that column. On failure, returns an error.
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * The file cannot be opened, as [`LladError::Io`].
+/// * There is an error reading the CSV headers or records, as [`LladError::Csv`].
+/// * There is a parse error while reading CSV data, as [`LladError::Parse`] with the column, row and field.
+pub fn read_csv_as_audio_data(filename: String) -> Result<AudioData, LladError> {
+    read_csv_with_dialect(filename, &CsvDialect::default())
+}
+
+/// Reads a CSV written in another [`CsvDialect`] than the default, see [`crate::SampleLogger::set_csv_dialect`],
+/// like [`read_csv_as_audio_data`] reads a CSV in the default dialect. Floats are read in every
+/// [`crate::FloatFormat`], and an index column is skipped.
+///
+/// Without a header, the keys of the columns are taken from the sidecar file, in the order in which
+/// [`crate::SampleLogger`] wrote them. Columns that are not in the sidecar file are named after their position,
+/// starting at `0`, and read as [`ColumnType::F32`].
+///
+/// # Arguments
+///
+/// * `filename` - A string representing the path to the CSV file.
+/// * `dialect` - The dialect the CSV was written in.
+///
+/// # Returns
+///
+/// * `Result<AudioData, LladError>` - On success, returns an [`AudioData`] with every column of the CSV except the
+///   index column. On failure, returns an error.
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * The file cannot be opened, as [`LladError::Io`].
+/// * There is an error reading the CSV headers or records, as [`LladError::Csv`].
+/// * There is a parse error while reading CSV data, as [`LladError::Parse`] with the column, row and field.
+pub fn read_csv_with_dialect(
+    filename: String,
+    dialect: &CsvDialect,
+) -> Result<AudioData, LladError> {
+    let (sidecar_types, metadata) = meta::read_meta(filename.as_str())?;
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(dialect.delimiter)
+        .has_headers(dialect.header)
+        .from_reader(File::open(filename.as_str())?);
+    let mut data = AudioData {
+        metadata,
+        ..AudioData::new()
+    };
+    let index_columns = usize::from(dialect.index != IndexColumn::None);
+
+    let columns = match dialect.header {
+        true => {
+            let mut sidecar_types: HashMap<String, ColumnType> =
+                sidecar_types.into_iter().collect();
+            reader
+                .headers()?
+                .iter()
+                .skip(index_columns)
+                .map(|header| {
+                    let column_type = sidecar_types.remove(header).unwrap_or(ColumnType::F32);
+                    (String::from(header), column_type)
+                })
+                .collect()
+        }
+        false => sidecar_types,
+    };
+    let mut types = Vec::new();
+
+    for (key, column_type) in columns {
+        data.push_column(key, ColumnData::with_capacity(column_type.clone(), 0));
+        types.push(column_type);
+    }
+
+    for (row, record) in reader.records().enumerate() {
+        let record = record?;
+
+        for (i, field) in record.iter().skip(index_columns).enumerate() {
+            if i == types.len() {
+                data.push_column(i.to_string(), ColumnData::with_capacity(ColumnType::F32, 0));
+                types.push(ColumnType::F32);
+            }
+
+            if field.is_empty() {
+                continue;
+            }
+
+            let value = types[i].parse(field).ok_or_else(|| LladError::Parse {
+                column: Arc::from(data.keys[i].as_str()),
+                row,
+                value: String::from(field),
+            })?;
+            data.columns[i].push(value);
+        }
+    }
+
+    Ok(data)
+}
+
+/// Writes columns to a CSV with their keys as the header, one row per frame. Fields of columns that are shorter than
+/// `rows` are left empty.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the CSV.
+/// * `columns`: The key and values of every column, in the order in which they are written.
+/// * `rows`: The number of rows to write.
+/// * `dialect`: The dialect to write the CSV in.
+/// * `metadata`: The metadata of the columns, for the index column.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the CSV is written, or the error while writing it.
+pub(crate) fn write_csv(
+    filename: &str,
+    columns: &[(&str, &ColumnData)],
+    rows: usize,
+    dialect: &CsvDialect,
+    metadata: &Metadata,
+) -> Result<(), LladError> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(dialect.delimiter)
+        .from_writer(File::create(filename)?);
+
+    if dialect.header {
+        let index = dialect.index.header(metadata.sample_rate);
+        writer.write_record(index.into_iter().chain(columns.iter().map(|(key, _)| *key)))?;
+    }
+
+    for row in 0..rows {
+        let mut record = csv::StringRecord::new();
+        let sample = metadata.first_sample + row as u64;
+        if let Some(index) = dialect
+            .index
+            .field(sample, metadata.sample_rate, dialect.floats)
+        {
+            record.push_field(index.as_str());
+        }
+        for (_, values) in columns {
+            let entry = values.format(row, dialect.floats).unwrap_or_default();
+            record.push_field(entry.as_str());
+        }
+        writer.write_record(&record)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! A minimal gain plugin that logs its gain parameter, run offline over a WAV file. Wrapping it in [`Probed`] also logs
+//! its input and output:
+//!
+//! ```sh
+//! cargo run --bin llad-gain -- input.wav output.wav --buffer-size 256
+//! ```
+//!
+//! This writes the processed audio to `output.wav` and the logged samples to `gain.csv`, with their types and the
+//! sample rate in `gain.csv.meta`. To run another plugin, copy this file and replace `Gain` with the plugin.
+
+use std::{num::NonZeroU32, sync::Arc};
+
+use llad::{LladError, Logged, ParamProbe, Probed, SampleLogger};
+use nih_plug::prelude::*;
+
+struct Gain {
+    params: Arc<GainParams>,
+    logger: SampleLogger,
+    param_probe: ParamProbe,
+}
+
+#[derive(Params)]
+struct GainParams {
+    #[id = "gain"]
+    gain: FloatParam,
+}
+
+impl Default for Gain {
+    fn default() -> Self {
+        let params = Arc::new(GainParams {
+            gain: FloatParam::new(
+                "Gain",
+                util::db_to_gain(-6.0),
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0)),
+        });
+
+        // Logs the columns `gain` and `gain.smoothed`.
+        let mut logger = SampleLogger::with_capacity(String::from("gain.csv"), &[], 48000);
+        let param_probe = ParamProbe::new(params.clone(), &mut logger);
+
+        Self {
+            params,
+            logger,
+            param_probe,
+        }
+    }
+}
+
+impl Plugin for Gain {
+    const NAME: &'static str = "LLAD Gain";
+    const VENDOR: &'static str = "LLAD";
+    const URL: &'static str = "https://github.com/PietPtr/LLAD";
+    const EMAIL: &'static str = "";
+    const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(2),
+            main_output_channels: NonZeroU32::new(2),
+            ..AudioIOLayout::const_default()
+        },
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(1),
+            main_output_channels: NonZeroU32::new(1),
+            ..AudioIOLayout::const_default()
+        },
+    ];
+
+    type SysExMessage = ();
+    type BackgroundTask = ();
+
+    fn params(&self) -> Arc<dyn Params> {
+        self.params.clone()
+    }
+
+    fn process(
+        &mut self,
+        buffer: &mut Buffer,
+        _aux: &mut AuxiliaryBuffers,
+        _context: &mut impl ProcessContext<Self>,
+    ) -> ProcessStatus {
+        for channel_samples in buffer.iter_samples() {
+            let gain = self.params.gain.smoothed.next();
+
+            for sample in channel_samples {
+                *sample *= gain;
+            }
+
+            self.param_probe.write(&mut self.logger).unwrap();
+            self.logger.end_frame().unwrap();
+        }
+
+        ProcessStatus::Normal
+    }
+}
+
+impl Logged for Gain {
+    fn sample_logger(&mut self) -> &mut SampleLogger {
+        &mut self.logger
+    }
+}
+
+fn main() -> Result<(), LladError> {
+    llad::run_cli::<Probed<Gain>>()
+}
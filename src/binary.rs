@@ -0,0 +1,745 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write},
+};
+
+use crate::{
+    audio_data, meta, read_csv_as_audio_data, AudioData, ColumnData, ColumnType, CsvDialect,
+    LladError, Metadata, Value,
+};
+
+/// The magic bytes at the start of every file in the LLAD binary format.
+const MAGIC: &[u8; 8] = b"LLADBIN\0";
+
+/// The version of the LLAD binary format that is written, and the newest one that can be read.
+const VERSION: u16 = 1;
+
+/// The offset of the number of frames in the header, which is filled in when the file is finished.
+const FRAMES_OFFSET: u64 = 16;
+
+/// The size of the fixed part of the header, before the schema.
+const HEADER_LEN: u64 = 24;
+
+/// The number of frames of a file that was not finished.
+const UNKNOWN_FRAMES: u64 = u64::MAX;
+
+/// The format of the file that a [`crate::SampleLogger`] writes, set with
+/// [`crate::SampleLogger::set_output_format`].
+///
+/// The LLAD binary format stores every value with all its bits, takes a fraction of the space of a CSV and can be
+/// read without parsing text. A file is laid out as follows, where all numbers are little-endian:
+///
+/// | Offset    | Size | Contents                                                                                  |
+/// |-----------|------|-------------------------------------------------------------------------------------------|
+/// | 0         | 8    | The magic bytes `LLADBIN\0`.                                                              |
+/// | 8         | 2    | The version of the format as a `u16`, currently 1.                                        |
+/// | 10        | 2    | Reserved, 0.                                                                              |
+/// | 12        | 4    | The length `n` of the schema as a `u32`.                                                  |
+/// | 16        | 8    | The number of frames as a `u64`, or `u64::MAX` if the file was never finished.            |
+/// | 24        | `n`  | The schema, see below.                                                                    |
+/// | `24 + n`  |      | Zero bytes up to the next multiple of 8, where the frames start.                          |
+/// | ...       |      | The frames, one after the other.                                                          |
+/// | ...       | 4    | The length `m` of the trailer as a `u32`.                                                 |
+/// | ...       | `m`  | The trailer, see below.                                                                   |
+///
+/// The schema is UTF-8 text in the format of the sidecar file of a CSV: records such as `sample_rate,48000` for the
+/// [`Metadata`] that was known when the file was started, and a `type,<key>,<type>[,<label>...]` record for every
+/// column, in the order of the values in a frame.
+///
+/// A frame holds the value of every column: `f32` and `f64` columns as IEEE 754 floats, `i64` columns as two's
+/// complement integers, `bool` columns as a single byte that is 0 or 1, and enum columns as the index of their variant
+/// as a `u32`. The values are packed in the order of the schema without any padding, so a value is not necessarily
+/// aligned to its size, e.g. a `bool` column followed by an `f32` column puts the float at offset 1 of the frame. Read
+/// values with unaligned loads, such as `f32::from_le_bytes`, or with a packed struct. Every frame is the same size,
+/// the sum of the sizes of its values, so frame `i` starts at `data_offset + i * frame_size`, see
+/// [`BinaryReader::data_offset`] and [`BinaryReader::frame_size`]. Only the first frame is aligned to 8 bytes.
+///
+/// The trailer holds the metadata records as they were when the file was finished, such as the frame in which a
+/// trigger fired. It replaces the metadata of the schema. A file that was never finished, e.g. because the plugin
+/// panicked while streaming, has no trailer and ends after its last complete frame, or in the middle of a frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A CSV with a header, and a sidecar file with the types of the columns and the metadata.
+    #[default]
+    Csv,
+    /// The LLAD binary format, which holds the types of the columns and the metadata itself.
+    Binary,
+}
+
+/// Writes a file in the LLAD binary format, see [`OutputFormat::Binary`], frame by frame.
+///
+/// # Fields
+///
+/// * `file`: The file.
+/// * `frames`: The number of frames written so far.
+pub(crate) struct BinaryWriter {
+    file: BufWriter<File>,
+    frames: u64,
+}
+
+impl BinaryWriter {
+    /// Creates the file and writes the header, with an unknown number of frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename`: The name of the file.
+    /// * `columns`: The key and type of every column, in the order of the values in a frame.
+    /// * `metadata`: The metadata known so far.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<BinaryWriter, LladError>`: The writer, or the error while creating the file or writing the header.
+    pub fn create(
+        filename: &str,
+        columns: &[(&str, ColumnType)],
+        metadata: &Metadata,
+    ) -> Result<Self, LladError> {
+        let mut schema = Vec::new();
+        meta::write_records(&mut schema, metadata, columns.iter().cloned())?;
+
+        let mut file = BufWriter::new(File::create(filename)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&VERSION.to_le_bytes())?;
+        file.write_all(&0_u16.to_le_bytes())?;
+        file.write_all(&(schema.len() as u32).to_le_bytes())?;
+        file.write_all(&UNKNOWN_FRAMES.to_le_bytes())?;
+        file.write_all(&schema)?;
+        file.write_all(&vec![0; padding(schema.len())])?;
+
+        Ok(Self { file, frames: 0 })
+    }
+
+    /// Writes the next value of the current frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `value`: The value, which must be of the type of its column.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the value is written, or the error while writing it.
+    pub fn write_value(&mut self, value: Value) -> Result<(), LladError> {
+        match value {
+            Value::F32(value) => self.file.write_all(&value.to_le_bytes())?,
+            Value::F64(value) => self.file.write_all(&value.to_le_bytes())?,
+            Value::I64(value) => self.file.write_all(&value.to_le_bytes())?,
+            Value::Bool(value) => self.file.write_all(&[u8::from(value)])?,
+            Value::Enum(value) => self.file.write_all(&value.to_le_bytes())?,
+        }
+
+        Ok(())
+    }
+
+    /// Ends the current frame, after the value of every column has been written to it.
+    pub fn end_frame(&mut self) {
+        self.frames += 1;
+    }
+
+    /// Writes all buffered frames to the file.
+    pub fn flush(&mut self) -> Result<(), LladError> {
+        self.file.flush()?;
+        Ok(())
+    }
+
+    /// Writes the trailer and the number of frames, after which the file is complete.
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata`: The metadata as it is when the file is finished.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the file is complete, or the error while writing it.
+    pub fn finish(mut self, metadata: &Metadata) -> Result<(), LladError> {
+        let mut trailer = Vec::new();
+        meta::write_records(&mut trailer, metadata, std::iter::empty())?;
+
+        self.file.write_all(&(trailer.len() as u32).to_le_bytes())?;
+        self.file.write_all(&trailer)?;
+        self.file.seek(SeekFrom::Start(FRAMES_OFFSET))?;
+        self.file.write_all(&self.frames.to_le_bytes())?;
+        self.file.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Finishes a file in the LLAD binary format that was streamed by the writer thread, see [`BinaryWriter::finish`].
+/// A frame that was only partially written is cut off.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the file.
+/// * `metadata`: The metadata as it is when the file is finished.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the file is complete, or the error while reading or writing it.
+pub(crate) fn finish_file(filename: &str, metadata: &Metadata) -> Result<(), LladError> {
+    let reader = BinaryReader::open(String::from(filename))?;
+    let frames = (reader.len - reader.data_offset)
+        .checked_div(reader.frame_size() as u64)
+        .unwrap_or(0);
+
+    let mut file = OpenOptions::new().write(true).open(filename)?;
+    file.set_len(reader.data_offset + frames * reader.frame_size() as u64)?;
+    file.seek(SeekFrom::End(0))?;
+
+    BinaryWriter {
+        file: BufWriter::new(file),
+        frames,
+    }
+    .finish(metadata)
+}
+
+/// Writes columns in the LLAD binary format, see [`OutputFormat::Binary`].
+///
+/// # Arguments
+///
+/// * `filename`: The name of the file.
+/// * `columns`: The key and values of every column, in the order of the values in a frame.
+/// * `rows`: The number of frames to write, which no column may be shorter than.
+/// * `metadata`: The metadata.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the file is written, or the error while writing it. Returns
+///   [`LladError::ColumnTooShort`] if a column has fewer than `rows` values.
+pub(crate) fn write_columns(
+    filename: &str,
+    columns: &[(&str, &ColumnData)],
+    rows: usize,
+    metadata: &Metadata,
+) -> Result<(), LladError> {
+    let types: Vec<(&str, ColumnType)> = columns
+        .iter()
+        .map(|(key, values)| (*key, values.column_type()))
+        .collect();
+    let mut writer = BinaryWriter::create(filename, &types, metadata)?;
+
+    for row in 0..rows {
+        for (key, values) in columns {
+            let value = values.get(row).ok_or_else(|| LladError::ColumnTooShort {
+                column: String::from(*key),
+                rows,
+            })?;
+            writer.write_value(value)?;
+        }
+        writer.end_frame();
+    }
+
+    writer.finish(metadata)
+}
+
+/// Writes columns to a file in the LLAD binary format, see [`OutputFormat::Binary`]. Rows in which not every column
+/// has a value are left out.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the file.
+/// * `data`: The columns to write, e.g. as read by [`read_csv_as_audio_data`].
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the file is written, or the error while writing it.
+pub fn write_binary(filename: String, data: &AudioData) -> Result<(), LladError> {
+    let columns: Vec<(&str, &ColumnData)> = data.iter().collect();
+    let rows = columns
+        .iter()
+        .map(|(_, values)| values.len())
+        .min()
+        .unwrap_or(0);
+
+    write_columns(filename.as_str(), &columns, rows, data.metadata())
+}
+
+/// Converts a CSV written by [`crate::SampleLogger`], with its sidecar file, to the LLAD binary format.
+///
+/// # Arguments
+///
+/// * `csv_file`: The name of the CSV.
+/// * `binary_file`: The name of the file in the LLAD binary format.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the file is converted, or the error while reading or writing it.
+pub fn csv_to_binary(csv_file: String, binary_file: String) -> Result<(), LladError> {
+    write_binary(binary_file, &read_csv_as_audio_data(csv_file)?)
+}
+
+/// Converts a file in the LLAD binary format to a CSV and its sidecar file, as if [`crate::SampleLogger`] had written
+/// the CSV.
+///
+/// # Arguments
+///
+/// * `binary_file`: The name of the file in the LLAD binary format.
+/// * `csv_file`: The name of the CSV.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the file is converted, or the error while reading or writing it.
+pub fn binary_to_csv(binary_file: String, csv_file: String) -> Result<(), LladError> {
+    let data = read_binary_as_audio_data(binary_file)?;
+    let columns: Vec<(&str, &ColumnData)> = data.iter().collect();
+    let rows = columns
+        .iter()
+        .map(|(_, values)| values.len())
+        .max()
+        .unwrap_or(0);
+
+    audio_data::write_csv(
+        csv_file.as_str(),
+        &columns,
+        rows,
+        &CsvDialect::default(),
+        data.metadata(),
+    )?;
+    meta::write_meta(
+        csv_file.as_str(),
+        data.metadata(),
+        data.iter().map(|(key, values)| (key, values.column_type())),
+    )
+}
+
+/// Reads a file in the LLAD binary format, see [`OutputFormat::Binary`], one frame at a time, so that captures that
+/// don't fit in memory can be processed. Use [`read_binary_as_audio_data`] to read all frames at once.
+///
+/// ```ignore
+/// let mut reader = llad::BinaryReader::open(String::from("capture.llad"))?;
+/// let mut frame = Vec::new();
+/// while reader.next_frame(&mut frame)? {
+///     // `frame` holds a value for every key in `reader.keys()`.
+/// }
+/// ```
+///
+/// # Fields
+///
+/// * `reader`: The file, positioned at the next frame.
+/// * `keys`: The key of every column, in the order of the values in a frame.
+/// * `types`: The type of every column.
+/// * `metadata`: The metadata of the schema, or of the trailer once all frames are read.
+/// * `frames`: The number of frames, `None` if the file was never finished.
+/// * `frames_read`: The number of frames read so far.
+/// * `data_offset`: The offset of the first frame from the start of the file.
+/// * `len`: The length of the file.
+/// * `frame`: The bytes of the frame that is read.
+/// * `finished`: Whether all frames have been read.
+pub struct BinaryReader {
+    reader: BufReader<File>,
+    keys: Vec<String>,
+    types: Vec<ColumnType>,
+    metadata: Metadata,
+    frames: Option<u64>,
+    frames_read: u64,
+    data_offset: u64,
+    len: u64,
+    frame: Vec<u8>,
+    finished: bool,
+}
+
+impl BinaryReader {
+    /// Opens a file in the LLAD binary format and reads its header.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - A string representing the path to the file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<BinaryReader, LladError>` - On success, returns the reader positioned at the first frame. On failure,
+    ///   returns an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    ///
+    /// * The file cannot be opened or read, as [`LladError::Io`].
+    /// * The file is not in the LLAD binary format or in a newer version, as [`LladError::InvalidBinary`].
+    /// * The schema can't be read, as [`LladError::Csv`].
+    pub fn open(filename: String) -> Result<Self, LladError> {
+        let file = File::open(filename)?;
+        let len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+
+        let mut header = [0; HEADER_LEN as usize];
+        reader
+            .read_exact(&mut header)
+            .map_err(|error| match error.kind() {
+                ErrorKind::UnexpectedEof => LladError::InvalidBinary("file is too short."),
+                _ => LladError::Io(error),
+            })?;
+        if &header[..8] != MAGIC {
+            return Err(LladError::InvalidBinary("wrong magic bytes."));
+        }
+        if u16::from_le_bytes([header[8], header[9]]) > VERSION {
+            return Err(LladError::InvalidBinary("unsupported version."));
+        }
+        let schema_len = u32::from_le_bytes(header[12..16].try_into().unwrap_or_default()) as usize;
+        let frames = u64::from_le_bytes(header[16..24].try_into().unwrap_or_default());
+
+        let mut schema = vec![0; schema_len + padding(schema_len)];
+        reader.read_exact(&mut schema)?;
+        let (columns, metadata) = meta::read_records(&schema[..schema_len])?;
+        let (keys, types): (Vec<String>, Vec<ColumnType>) = columns.into_iter().unzip();
+        let frame_size = types.iter().map(value_size).sum();
+
+        Ok(Self {
+            reader,
+            keys,
+            types,
+            metadata,
+            frames: (frames != UNKNOWN_FRAMES).then_some(frames),
+            frames_read: 0,
+            data_offset: HEADER_LEN + schema.len() as u64,
+            len,
+            frame: vec![0; frame_size],
+            finished: false,
+        })
+    }
+
+    /// Returns the key of every column, in the order of the values in a frame.
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Returns the type of every column, in the order of the values in a frame.
+    pub fn column_types(&self) -> &[ColumnType] {
+        &self.types
+    }
+
+    /// Returns the metadata of the file. Until [`BinaryReader::next_frame`] has returned `false`, this is the metadata
+    /// that was known when the file was started. Afterwards, it is the metadata of the trailer, or has
+    /// [`Metadata::truncated`] set if the file was never finished.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns the number of frames in the file, or `None` if the file was never finished.
+    pub fn frames(&self) -> Option<u64> {
+        self.frames
+    }
+
+    /// Returns the offset of the first frame from the start of the file, which is a multiple of 8.
+    pub fn data_offset(&self) -> u64 {
+        self.data_offset
+    }
+
+    /// Returns the size of a frame in bytes, the sum of the sizes of its values as they are packed without padding.
+    pub fn frame_size(&self) -> usize {
+        self.frame.len()
+    }
+
+    /// Reads the next frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `values`: Where the value of every column is stored, in the order of [`BinaryReader::keys`]. Its previous
+    ///   contents are replaced, so reusing it for every frame doesn't allocate.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool, LladError>`: `true` if a frame was read, `false` if all frames have been read. Returns `Err` if
+    ///   the file can't be read.
+    pub fn next_frame(&mut self, values: &mut Vec<Value>) -> Result<bool, LladError> {
+        if self.finished {
+            return Ok(false);
+        }
+
+        if self.frames == Some(self.frames_read) {
+            self.read_trailer()?;
+            self.finished = true;
+            return Ok(false);
+        }
+
+        let read = match self.frame.is_empty() && self.frames.is_none() {
+            // A file without columns that was never finished has no way to tell how many frames it has.
+            true => Err(ErrorKind::UnexpectedEof.into()),
+            false => self.reader.read_exact(&mut self.frame),
+        };
+        match read {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof && self.frames.is_none() => {
+                self.metadata.truncated = true;
+                self.finished = true;
+                return Ok(false);
+            }
+            Err(error) => return Err(error.into()),
+        }
+        self.frames_read += 1;
+
+        values.clear();
+        let mut offset = 0;
+        for column_type in &self.types {
+            let size = value_size(column_type);
+            values.push(decode(column_type, &self.frame[offset..offset + size]));
+            offset += size;
+        }
+
+        Ok(true)
+    }
+
+    /// Reads the trailer after the last frame, and replaces the metadata with it.
+    fn read_trailer(&mut self) -> Result<(), LladError> {
+        let mut len = [0; 4];
+        self.reader.read_exact(&mut len)?;
+
+        let mut trailer = vec![0; u32::from_le_bytes(len) as usize];
+        self.reader.read_exact(&mut trailer)?;
+        (_, self.metadata) = meta::read_records(trailer.as_slice())?;
+
+        Ok(())
+    }
+}
+
+/// Reads a file in the LLAD binary format, see [`OutputFormat::Binary`], into an [`AudioData`], like
+/// [`read_csv_as_audio_data`] reads a CSV. The columns are in the order of the file, and the [`Metadata`] is the one
+/// of its trailer. A file that was never finished is read up to its last complete frame, with
+/// [`Metadata::truncated`] set.
+///
+/// # Arguments
+///
+/// * `filename` - A string representing the path to the file.
+///
+/// # Returns
+///
+/// * `Result<AudioData, LladError>` - On success, returns an [`AudioData`] with every column of the file. On failure,
+///   returns an error.
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * The file cannot be opened or read, as [`LladError::Io`].
+/// * The file is not in the LLAD binary format or in a newer version, as [`LladError::InvalidBinary`].
+/// * The schema or trailer can't be read, as [`LladError::Csv`].
+pub fn read_binary_as_audio_data(filename: String) -> Result<AudioData, LladError> {
+    let mut reader = BinaryReader::open(filename)?;
+    let capacity = match reader.frames() {
+        Some(frames) => frames as usize,
+        None => ((reader.len - reader.data_offset) as usize)
+            .checked_div(reader.frame_size())
+            .unwrap_or(0),
+    };
+
+    let mut data = AudioData::new();
+    for (key, column_type) in reader.keys().iter().zip(reader.column_types()) {
+        data.push_column(
+            key.clone(),
+            ColumnData::with_capacity(column_type.clone(), capacity),
+        );
+    }
+
+    let mut frame = Vec::with_capacity(data.len());
+    while reader.next_frame(&mut frame)? {
+        for (column, &value) in data.columns.iter_mut().zip(&frame) {
+            column.push(value);
+        }
+    }
+    data.metadata = reader.metadata().clone();
+
+    Ok(data)
+}
+
+/// Returns the number of zero bytes after a schema of `len` bytes, so that the frames start at a multiple of 8.
+fn padding(len: usize) -> usize {
+    (8 - len % 8) % 8
+}
+
+/// Returns the size of a value of a column in a frame.
+fn value_size(column_type: &ColumnType) -> usize {
+    match column_type {
+        ColumnType::F32 | ColumnType::Enum(_) => 4,
+        ColumnType::F64 | ColumnType::I64 => 8,
+        ColumnType::Bool => 1,
+    }
+}
+
+/// Decodes a value of a column from its bytes in a frame.
+///
+/// # Arguments
+///
+/// * `column_type`: The type of the column.
+/// * `bytes`: The bytes of the value, as many as [`value_size`] of the type.
+///
+/// # Returns
+///
+/// * `Value`: The value.
+fn decode(column_type: &ColumnType, bytes: &[u8]) -> Value {
+    let word = |bytes: &[u8]| -> [u8; 4] { bytes.try_into().unwrap_or_default() };
+    let double_word = |bytes: &[u8]| -> [u8; 8] { bytes.try_into().unwrap_or_default() };
+
+    match column_type {
+        ColumnType::F32 => Value::F32(f32::from_le_bytes(word(bytes))),
+        ColumnType::F64 => Value::F64(f64::from_le_bytes(double_word(bytes))),
+        ColumnType::I64 => Value::I64(i64::from_le_bytes(double_word(bytes))),
+        ColumnType::Bool => Value::Bool(bytes.first().is_some_and(|&byte| byte != 0)),
+        ColumnType::Enum(_) => Value::Enum(u32::from_le_bytes(word(bytes))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SampleLogger;
+
+    fn temp_file(name: &str) -> String {
+        std::env::temp_dir()
+            .join(name)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Logs a column of every type, with a `bool` column first so that the values after it are not aligned, and
+    /// streams them with the given capacity if there is one.
+    fn log(logger: &mut SampleLogger, stream: Option<usize>) {
+        let gate = logger.register_typed("gate", ColumnType::Bool);
+        let input = logger.register("input");
+        let gain = logger.register_typed("gain", ColumnType::F64);
+        let count = logger.register_typed("count", ColumnType::I64);
+        let state = logger.register_typed("state", ColumnType::enumeration(&["a", "b"]));
+        logger.set_sample_rate(44100.0);
+        if let Some(capacity) = stream {
+            logger.start_streaming(capacity).unwrap();
+        }
+
+        for i in 0..5 {
+            logger.write_column(gate, i % 2 == 0).unwrap();
+            logger
+                .write_column(input, [0.25_f32, -1e-40, f32::MAX][i % 3])
+                .unwrap();
+            logger.write_column(gain, [0.1, 5e-324][i % 2]).unwrap();
+            logger.write_column(count, -(i as i64)).unwrap();
+            logger
+                .write_column(state, Value::Enum((i % 2) as u32))
+                .unwrap();
+            logger.end_frame().unwrap();
+        }
+    }
+
+    fn columns() -> Vec<(&'static str, ColumnType)> {
+        vec![("gate", ColumnType::Bool), ("input", ColumnType::F32)]
+    }
+
+    #[test]
+    fn frames_are_packed() {
+        let filename = temp_file("llad_packed.bin");
+        let mut logger = SampleLogger::new(filename.clone());
+        logger.set_output_format(OutputFormat::Binary);
+        log(&mut logger, None);
+        logger.write_debug_values().unwrap();
+
+        let reader = BinaryReader::open(filename.clone()).unwrap();
+        assert_eq!(reader.frames(), Some(5));
+        assert_eq!(reader.data_offset() % 8, 0);
+        assert_eq!(reader.frame_size(), 1 + 4 + 8 + 8 + 4);
+
+        let bytes = std::fs::read(filename).unwrap();
+        let frame = &bytes[reader.data_offset() as usize..][..reader.frame_size()];
+        assert_eq!(frame[0], 1);
+        assert_eq!(frame[1..5], 0.25_f32.to_le_bytes());
+        assert_eq!(frame[5..13], 0.1_f64.to_le_bytes());
+    }
+
+    #[test]
+    fn logged_columns_are_read_back() {
+        let filename = temp_file("llad_round_trip.bin");
+        let mut logger = SampleLogger::new(filename.clone());
+        logger.set_output_format(OutputFormat::Binary);
+        log(&mut logger, None);
+        let expected = logger.captured().unwrap();
+        logger.write_debug_values().unwrap();
+
+        let data = read_binary_as_audio_data(filename.clone()).unwrap();
+        assert_eq!(data.keys(), expected.keys());
+        for (key, values) in expected.iter() {
+            assert_eq!(data.get(key), Some(values), "{key}");
+        }
+        assert_eq!(data.metadata().sample_rate, Some(44100.0));
+        assert!(!data.metadata().truncated);
+
+        let csv = temp_file("llad_round_trip.csv");
+        let copy = temp_file("llad_round_trip_copy.bin");
+        binary_to_csv(filename, csv.clone()).unwrap();
+        csv_to_binary(csv, copy.clone()).unwrap();
+        let copied = read_binary_as_audio_data(copy).unwrap();
+        for (key, values) in expected.iter() {
+            assert_eq!(copied.get(key), Some(values), "{key}");
+        }
+    }
+
+    #[test]
+    fn streamed_file_is_read_back() {
+        let filename = temp_file("llad_streamed.bin");
+        let mut logger = SampleLogger::new(filename.clone());
+        logger.set_output_format(OutputFormat::Binary);
+        log(&mut logger, Some(64));
+        logger.write_debug_values().unwrap();
+
+        let reader = BinaryReader::open(filename.clone()).unwrap();
+        assert_eq!(reader.frames(), Some(5));
+        let data = read_binary_as_audio_data(filename).unwrap();
+        assert_eq!(
+            data.get("count").unwrap().as_i64(),
+            Some(&[0, -1, -2, -3, -4][..])
+        );
+    }
+
+    /// Writes two frames and half of a third one without finishing the file, as if the plugin had crashed.
+    fn write_truncated(filename: &str) {
+        let mut writer = BinaryWriter::create(filename, &columns(), &Metadata::default()).unwrap();
+        for i in 0..2 {
+            writer.write_value(Value::Bool(i == 0)).unwrap();
+            writer.write_value(Value::F32(i as f32)).unwrap();
+            writer.end_frame();
+        }
+        writer.write_value(Value::Bool(true)).unwrap();
+        writer.flush().unwrap();
+    }
+
+    #[test]
+    fn truncated_capture_is_read_up_to_its_last_frame() {
+        let filename = temp_file("llad_truncated.bin");
+        write_truncated(&filename);
+
+        let reader = BinaryReader::open(filename.clone()).unwrap();
+        assert_eq!(reader.frames(), None);
+
+        let data = read_binary_as_audio_data(filename).unwrap();
+        assert!(data.metadata().truncated);
+        assert_eq!(
+            data.get("gate").unwrap().as_bool(),
+            Some(&[true, false][..])
+        );
+        assert_eq!(data.get("input").unwrap().as_f32(), Some(&[0.0, 1.0][..]));
+    }
+
+    #[test]
+    fn finishing_a_truncated_capture_cuts_off_the_partial_frame() {
+        let filename = temp_file("llad_finished.bin");
+        write_truncated(&filename);
+
+        let metadata = Metadata {
+            sample_rate: Some(48000.0),
+            ..Default::default()
+        };
+        finish_file(&filename, &metadata).unwrap();
+
+        let reader = BinaryReader::open(filename.clone()).unwrap();
+        assert_eq!(reader.frames(), Some(2));
+        let data = read_binary_as_audio_data(filename).unwrap();
+        assert!(!data.metadata().truncated);
+        assert_eq!(data.metadata().sample_rate, Some(48000.0));
+        assert_eq!(data.get("input").unwrap().as_f32(), Some(&[0.0, 1.0][..]));
+    }
+
+    #[test]
+    fn other_files_are_rejected() {
+        let filename = temp_file("llad_not_binary.bin");
+        std::fs::write(&filename, b"sample,x\n0,1\n").unwrap();
+
+        assert!(matches!(
+            BinaryReader::open(filename),
+            Err(LladError::InvalidBinary(_))
+        ));
+    }
+}
@@ -0,0 +1,260 @@
+use crate::Value;
+
+/// The frames of a block of samples that are filled out of order, e.g. the input of every frame before `process` and
+/// the output of every frame after it, and handed to the [`crate::SampleLogger`] in order once the block ends.
+///
+/// # Fields
+///
+/// * `values`: The values of every frame, `width` values per frame, or `None` for values that have not been written.
+/// * `width`: The number of columns in every frame.
+/// * `len`: The number of frames in the current block.
+/// * `frame`: The frame of the block that [`crate::SampleLogger::write_column`] writes to.
+/// * `active`: Whether a block has been started and not ended yet.
+#[derive(Default)]
+pub(crate) struct Block {
+    values: Vec<Option<Value>>,
+    width: usize,
+    len: usize,
+    pub frame: usize,
+    pub active: bool,
+}
+
+impl Block {
+    /// Makes sure that a block of `len` frames of `width` columns can be started without allocating.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`: The number of frames in the block.
+    /// * `width`: The number of columns in every frame.
+    pub fn reserve(&mut self, len: usize, width: usize) {
+        if self.values.len() < len * width {
+            self.values.resize(len * width, None);
+        }
+    }
+
+    /// Starts a block, only allocates if the block is larger than what has been reserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`: The number of frames in the block.
+    /// * `width`: The number of columns in every frame.
+    pub fn begin(&mut self, len: usize, width: usize) {
+        self.reserve(len, width);
+        self.values[..len * width].fill(None);
+        self.width = width;
+        self.len = len;
+        self.frame = 0;
+        self.active = true;
+    }
+
+    /// Adds columns to the current block, for columns that are registered while it is active. The values that are
+    /// already in the block are kept. Allocates.
+    ///
+    /// # Arguments
+    ///
+    /// * `width`: The new number of columns in every frame.
+    pub fn widen(&mut self, width: usize) {
+        if width <= self.width {
+            return;
+        }
+
+        let old = self.width;
+        self.reserve(self.len, width);
+        // Moved from the last value to the first, as every value moves to a higher index.
+        for frame in (0..self.len).rev() {
+            for column in (0..width).rev() {
+                self.values[frame * width + column] = match column < old {
+                    true => self.values[frame * old + column],
+                    false => None,
+                };
+            }
+        }
+        self.width = width;
+    }
+
+    /// Drops all frames of the block, e.g. after an error while logging them.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.frame = 0;
+    }
+
+    /// Returns the number of frames in the current block.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of columns in every frame of the current block.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the index of a value in `values`, or `None` if it is outside of the block.
+    fn index(&self, frame: usize, column: usize) -> Option<usize> {
+        (frame < self.len && column < self.width).then_some(frame * self.width + column)
+    }
+
+    /// Stores a value in a frame of the block.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`: The index of the frame in the block.
+    /// * `column`: The index of the column.
+    /// * `value`: The value.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<bool>`: `None` if the frame or column is outside of the block, otherwise whether the value is stored,
+    ///   which it is not if this column already has a value in this frame.
+    pub fn write(&mut self, frame: usize, column: usize, value: Value) -> Option<bool> {
+        let index = self.index(frame, column)?;
+
+        if self.values[index].is_some() {
+            return Some(false);
+        }
+
+        self.values[index] = Some(value);
+        Some(true)
+    }
+
+    /// Returns a value in a frame of the block, or `None` if it has not been written.
+    pub fn get(&self, frame: usize, column: usize) -> Option<Value> {
+        self.values[self.index(frame, column)?]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LladError, SampleLogger};
+
+    #[test]
+    fn values_are_kept_per_frame_and_column() {
+        let mut block = Block::default();
+        block.begin(2, 2);
+
+        assert_eq!(block.write(1, 0, Value::F32(1.0)), Some(true));
+        assert_eq!(block.write(1, 0, Value::F32(2.0)), Some(false));
+        assert_eq!(block.write(2, 0, Value::F32(3.0)), None);
+        assert_eq!(block.write(0, 2, Value::F32(3.0)), None);
+        assert_eq!(block.get(1, 0), Some(Value::F32(1.0)));
+        assert_eq!(block.get(0, 0), None);
+
+        block.begin(2, 2);
+        assert_eq!(block.get(1, 0), None);
+    }
+
+    #[test]
+    fn widening_keeps_the_values() {
+        let mut block = Block::default();
+        block.begin(2, 2);
+        block.write(0, 0, Value::F32(1.0));
+        block.write(1, 1, Value::F32(2.0));
+
+        block.widen(3);
+        assert_eq!(block.width(), 3);
+        assert_eq!(block.get(0, 0), Some(Value::F32(1.0)));
+        assert_eq!(block.get(1, 1), Some(Value::F32(2.0)));
+        assert_eq!(block.get(0, 1), None);
+        assert_eq!(block.get(1, 2), None);
+        assert_eq!(block.write(1, 2, Value::F32(3.0)), Some(true));
+    }
+
+    /// Creates a logger with the columns `input` and `output`, and logs a block of three frames in which the outputs
+    /// are written before the inputs, in reverse order.
+    fn logger_with_block(filename: &str) -> SampleLogger {
+        let filename = std::env::temp_dir().join(filename);
+        let mut logger = SampleLogger::new(filename.to_string_lossy().into_owned());
+        let input = logger.register("input");
+        let output = logger.register("output");
+
+        logger.begin_block(3).unwrap();
+        for frame in (0..3).rev() {
+            logger.write_at(output, frame, frame as f32 * 10.0).unwrap();
+        }
+        for frame in 0..3 {
+            logger.write_column(input, frame as f32).unwrap();
+            logger.end_frame().unwrap();
+        }
+        logger.end_block().unwrap();
+
+        logger
+    }
+
+    fn column(logger: &SampleLogger, key: &str) -> Vec<f32> {
+        let values = logger.debug_values().get(key).unwrap();
+        values.as_f32().unwrap().to_vec()
+    }
+
+    #[test]
+    fn committed_block_is_logged_in_order() {
+        let logger = logger_with_block("llad_block.csv");
+
+        assert_eq!(column(&logger, "input"), [0.0, 1.0, 2.0]);
+        assert_eq!(column(&logger, "output"), [0.0, 10.0, 20.0]);
+    }
+
+    #[test]
+    fn discarded_block_is_not_logged() {
+        let mut logger = logger_with_block("llad_block_discard.csv");
+        let input = logger.column("input").unwrap();
+        let output = logger.column("output").unwrap();
+
+        logger.begin_block(2).unwrap();
+        logger.write_at(input, 0, 5.0_f32).unwrap();
+        logger.discard_block();
+
+        logger.begin_block(1).unwrap();
+        logger.write_at(input, 0, 3.0_f32).unwrap();
+        logger.write_at(output, 0, 30.0_f32).unwrap();
+        logger.end_block().unwrap();
+
+        assert_eq!(column(&logger, "input"), [0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(column(&logger, "output"), [0.0, 10.0, 20.0, 30.0]);
+    }
+
+    #[test]
+    fn incomplete_frame_drops_the_rest_of_the_block() {
+        let mut logger = logger_with_block("llad_block_incomplete.csv");
+        let input = logger.column("input").unwrap();
+        let output = logger.column("output").unwrap();
+
+        logger.begin_block(3).unwrap();
+        for frame in 0..3 {
+            logger.write_at(input, frame, 3.0 + frame as f32).unwrap();
+        }
+        logger.write_at(output, 0, 30.0_f32).unwrap();
+        logger.write_at(output, 2, 50.0_f32).unwrap();
+        assert!(matches!(
+            logger.end_block(),
+            Err(LladError::IncompleteFrame { .. })
+        ));
+
+        logger.begin_block(1).unwrap();
+        logger.write_at(input, 0, 6.0_f32).unwrap();
+        logger.write_at(output, 0, 60.0_f32).unwrap();
+        logger.end_block().unwrap();
+
+        assert_eq!(column(&logger, "input"), [0.0, 1.0, 2.0, 3.0, 6.0]);
+        assert_eq!(column(&logger, "output"), [0.0, 10.0, 20.0, 30.0, 60.0]);
+    }
+
+    #[test]
+    fn columns_created_in_the_first_block_are_added_to_it() {
+        let filename = std::env::temp_dir().join("llad_block_widen.csv");
+        let mut logger = SampleLogger::new(filename.to_string_lossy().into_owned());
+        let input = logger.register("input");
+
+        logger.begin_block(2).unwrap();
+        for frame in 0..2 {
+            logger.write_at(input, frame, frame as f32).unwrap();
+        }
+        for frame in 0..2 {
+            logger.write("late", frame as f32 * 10.0).unwrap();
+            logger.end_frame().unwrap();
+        }
+        logger.end_block().unwrap();
+
+        assert_eq!(column(&logger, "input"), [0.0, 1.0]);
+        assert_eq!(column(&logger, "late"), [0.0, 10.0]);
+    }
+}
@@ -0,0 +1,45 @@
+/// The key of the column with the index of the buffer of every frame, see [`crate::SampleLogger::log_buffers`].
+pub(crate) const INDEX_KEY: &str = "buffer.index";
+/// The key of the column with the offset of every frame in its buffer.
+pub(crate) const OFFSET_KEY: &str = "buffer.offset";
+/// The key of the column with the size of the buffer of every frame.
+pub(crate) const SIZE_KEY: &str = "buffer.size";
+
+/// The position of the current frame in the buffers passed to `Plugin::process`, as marked by
+/// [`crate::SampleLogger::begin_buffer`]. Frames before the first marked buffer are in buffer 0 of size 0.
+///
+/// # Fields
+///
+/// * `index`: The index of the current buffer.
+/// * `offset`: The offset of the current frame in the current buffer.
+/// * `size`: The number of samples in the current buffer.
+/// * `started`: Whether a buffer has been marked, after which the next one gets the next index.
+#[derive(Default)]
+pub(crate) struct BufferPosition {
+    pub index: u64,
+    pub offset: u64,
+    pub size: u64,
+    started: bool,
+}
+
+impl BufferPosition {
+    /// Moves on to the first frame of the next buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `size`: The number of samples in the buffer.
+    pub fn begin(&mut self, size: usize) {
+        if self.started {
+            self.index += 1;
+        }
+
+        self.started = true;
+        self.offset = 0;
+        self.size = size as u64;
+    }
+
+    /// Moves on to the next frame of the current buffer.
+    pub fn advance(&mut self) {
+        self.offset += 1;
+    }
+}
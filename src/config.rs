@@ -0,0 +1,18 @@
+use nih_plug::prelude::*;
+
+use crate::SampleLogger;
+
+impl SampleLogger {
+    /// Records the sample rate and maximum buffer size from the `BufferConfig` passed to `Plugin::initialize`, and the
+    /// name and version of the plugin, in the sidecar file, see [`crate::Metadata`]. [`crate::Probed`] calls this for
+    /// the plugin it wraps.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer_config`: The buffer configuration passed to `Plugin::initialize`.
+    pub fn set_buffer_config<P: Plugin>(&mut self, buffer_config: &BufferConfig) {
+        self.set_sample_rate(buffer_config.sample_rate);
+        self.set_buffer_size(buffer_config.max_buffer_size as usize);
+        self.set_plugin(P::NAME, P::VERSION);
+    }
+}
@@ -0,0 +1,360 @@
+/// How the CSV of a [`crate::SampleLogger`] is written, set with [`crate::SampleLogger::set_csv_dialect`], and how
+/// [`crate::read_csv_with_dialect`] reads it. The default is the dialect of [`crate::read_csv_as_audio_data`]: comma
+/// separated, with a header, floats in their shortest exact notation and no index column.
+///
+/// ```ignore
+/// logger.set_csv_dialect(llad::CsvDialect {
+///     delimiter: b'\t',
+///     floats: llad::FloatFormat::Hex,
+///     index: llad::IndexColumn::Time,
+///     ..Default::default()
+/// });
+/// ```
+///
+/// # Fields
+///
+/// * `delimiter`: The byte between two fields, e.g. `b';'` for spreadsheets in locales with a decimal comma.
+/// * `header`: Whether the first record holds the keys of the columns. Without it, the reader takes the keys from the
+///   sidecar file.
+/// * `floats`: How float values are written. The reader accepts every [`FloatFormat`] regardless of this field.
+/// * `index`: An extra first column with the sample index or time of every row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    pub header: bool,
+    pub floats: FloatFormat,
+    pub index: IndexColumn,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            header: true,
+            floats: FloatFormat::default(),
+            index: IndexColumn::default(),
+        }
+    }
+}
+
+/// How float values are written to a CSV, see [`CsvDialect::floats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// The shortest decimal notation that reads back as exactly the same float, e.g. `0.1` or `100000000`.
+    #[default]
+    Shortest,
+    /// Decimal notation with this many digits after the decimal point, e.g. `0.100` for 3. Not exact.
+    Fixed(usize),
+    /// Scientific notation, e.g. `1e-1`, with this many digits after the decimal point or with the shortest exact
+    /// mantissa for `None`.
+    Scientific(Option<usize>),
+    /// Hexadecimal notation as in C's `%a`, e.g. `0x1.99999ap-4`, which writes the bits of the float exactly and is
+    /// read by e.g. Python's `float.fromhex` and NumPy.
+    Hex,
+}
+
+/// An extra first column of a CSV, see [`CsvDialect::index`]. Its values are counted from the first frame the logger
+/// saw, so they match [`crate::Metadata::first_sample`] and [`crate::AudioData::time_axis`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndexColumn {
+    /// No index column.
+    #[default]
+    None,
+    /// A column named `sample` with the index of the frame of every row.
+    Sample,
+    /// A column named `time` with the time of every row in seconds. Without a known sample rate, this is a `sample`
+    /// column instead.
+    Time,
+}
+
+impl FloatFormat {
+    /// Formats an `f32` value.
+    pub(crate) fn format_f32(self, value: f32) -> String {
+        match self {
+            FloatFormat::Shortest => value.to_string(),
+            FloatFormat::Fixed(precision) => format!("{value:.precision$}"),
+            FloatFormat::Scientific(None) => format!("{value:e}"),
+            FloatFormat::Scientific(Some(precision)) => format!("{value:.precision$e}"),
+            // Every f32 is exactly an f64, and trailing zeros are trimmed from the mantissa.
+            FloatFormat::Hex => format_hex(f64::from(value)),
+        }
+    }
+
+    /// Formats an `f64` value.
+    pub(crate) fn format_f64(self, value: f64) -> String {
+        match self {
+            FloatFormat::Shortest => value.to_string(),
+            FloatFormat::Fixed(precision) => format!("{value:.precision$}"),
+            FloatFormat::Scientific(None) => format!("{value:e}"),
+            FloatFormat::Scientific(Some(precision)) => format!("{value:.precision$e}"),
+            FloatFormat::Hex => format_hex(value),
+        }
+    }
+}
+
+impl IndexColumn {
+    /// Returns the header of the index column.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate`: The sample rate of the columns, if known.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&'static str>`: The header, or `None` if there is no index column.
+    pub(crate) fn header(self, sample_rate: Option<f32>) -> Option<&'static str> {
+        match (self, sample_rate) {
+            (IndexColumn::None, _) => None,
+            (IndexColumn::Time, Some(_)) => Some("time"),
+            _ => Some("sample"),
+        }
+    }
+
+    /// Returns the field of the index column of a row.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample`: The index of the frame of the row.
+    /// * `sample_rate`: The sample rate of the columns, if known.
+    /// * `floats`: How the time is formatted.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>`: The field, or `None` if there is no index column.
+    pub(crate) fn field(
+        self,
+        sample: u64,
+        sample_rate: Option<f32>,
+        floats: FloatFormat,
+    ) -> Option<String> {
+        match (self, sample_rate) {
+            (IndexColumn::None, _) => None,
+            (IndexColumn::Time, Some(sample_rate)) => {
+                Some(floats.format_f64(sample as f64 / f64::from(sample_rate)))
+            }
+            _ => Some(sample.to_string()),
+        }
+    }
+}
+
+/// Formats a float in hexadecimal notation, see [`FloatFormat::Hex`]. NaN and the infinities are written as by
+/// `to_string`.
+fn format_hex(value: f64) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+
+    let sign = if value.is_sign_negative() { "-" } else { "" };
+    let bits = value.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i32;
+    let mantissa = bits & ((1 << 52) - 1);
+
+    let (leading, exponent) = match (exponent, mantissa) {
+        (0, 0) => return format!("{sign}0x0p+0"),
+        // Subnormal numbers have no implicit leading 1.
+        (0, _) => (0, -1022),
+        _ => (1, exponent - 1023),
+    };
+
+    let fraction = format!("{mantissa:013x}");
+    let fraction = fraction.trim_end_matches('0');
+    match fraction.is_empty() {
+        true => format!("{sign}0x{leading}p{exponent:+}"),
+        false => format!("{sign}0x{leading}.{fraction}p{exponent:+}"),
+    }
+}
+
+/// Parses a float in hexadecimal notation, see [`FloatFormat::Hex`], such as `0x1.8p+1` or `-0x0.4p-1022`.
+///
+/// # Arguments
+///
+/// * `field`: The field.
+///
+/// # Returns
+///
+/// * `Option<f64>`: The float, or `None` if the field is not in hexadecimal notation or has more digits than fit in
+///   an `f64` exactly.
+pub(crate) fn parse_hex(field: &str) -> Option<f64> {
+    let (negative, rest) = match field.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, field.strip_prefix('+').unwrap_or(field)),
+    };
+    let rest = rest
+        .strip_prefix("0x")
+        .or_else(|| rest.strip_prefix("0X"))?;
+    let (digits, exponent) = rest.split_once(['p', 'P']).unwrap_or((rest, "0"));
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+    let digits = format!("{integer}{fraction}");
+    if digits.is_empty() || digits.len() > 14 {
+        return None;
+    }
+    let mantissa = u64::from_str_radix(&digits, 16).ok()?;
+    let exponent = exponent.parse::<i32>().ok()? - 4 * fraction.len() as i32;
+
+    let magnitude = scale(mantissa as f64, exponent);
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// Multiplies a float by a power of two in steps, so that the intermediate results don't overflow or underflow before
+/// the result does.
+fn scale(mut value: f64, mut exponent: i32) -> f64 {
+    while exponent > 1000 {
+        value *= 2.0_f64.powi(1000);
+        exponent -= 1000;
+    }
+    while exponent < -1000 {
+        value *= 2.0_f64.powi(-1000);
+        exponent += 1000;
+    }
+
+    value * 2.0_f64.powi(exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_csv_with_dialect, AudioData, ColumnType, SampleLogger, Value};
+
+    const F32_VALUES: [f32; 6] = [0.1, -1.5e30, f32::MIN_POSITIVE / 3.0, f32::MAX, -0.0, 1.0];
+    const F64_VALUES: [f64; 6] = [0.1, -1.5e300, 5e-324, f64::MIN_POSITIVE / 3.0, -0.0, 1e-7];
+
+    #[test]
+    fn hex_is_exact() {
+        assert_eq!(format_hex(1.0), "0x1p+0");
+        assert_eq!(format_hex(-0.1), "-0x1.999999999999ap-4");
+        assert_eq!(format_hex(5e-324), "0x0.0000000000001p-1022");
+        assert_eq!(FloatFormat::Hex.format_f32(0.1), "0x1.99999ap-4");
+
+        for value in F64_VALUES.into_iter().chain([f64::MAX, f64::MIN_POSITIVE]) {
+            let parsed = parse_hex(&format_hex(value)).unwrap();
+            assert_eq!(parsed.to_bits(), value.to_bits(), "{value:e}");
+        }
+        for value in F32_VALUES {
+            let field = FloatFormat::Hex.format_f32(value);
+            let parsed = ColumnType::F32.parse(&field);
+            assert_eq!(
+                parsed.map(|v| v.to_bits()),
+                Some(value.to_bits() as u64),
+                "{value:e}"
+            );
+        }
+    }
+
+    #[test]
+    fn hex_parser_accepts_c_notation() {
+        assert_eq!(parse_hex("0x1.8p+1"), Some(3.0));
+        assert_eq!(parse_hex("0X1P-2"), Some(0.25));
+        assert_eq!(parse_hex("+0x10"), Some(16.0));
+        assert_eq!(parse_hex("-0x0.4p-1022"), Some(-f64::MIN_POSITIVE / 4.0));
+        assert_eq!(parse_hex("1.5"), None);
+        assert_eq!(parse_hex("0x"), None);
+        assert_eq!(parse_hex("0x1.000000000000001p+0"), None);
+    }
+
+    /// Logs a column of every type in a dialect, and reads it back in the same dialect.
+    fn round_trip(filename: &str, dialect: CsvDialect) -> AudioData {
+        let filename = std::env::temp_dir().join(filename);
+        let filename = filename.to_string_lossy().into_owned();
+
+        let mut logger = SampleLogger::new(filename.clone());
+        logger.set_csv_dialect(dialect);
+        logger.set_sample_rate(48000.0);
+        for (i, (f32_value, f64_value)) in F32_VALUES.into_iter().zip(F64_VALUES).enumerate() {
+            logger.write("f32", f32_value).unwrap();
+            logger.write("f64", f64_value).unwrap();
+            logger.write("i64", i as i64 - 3).unwrap();
+            logger.write("bool", i % 2 == 0).unwrap();
+            logger.end_frame().unwrap();
+        }
+        logger.write_debug_values().unwrap();
+
+        read_csv_with_dialect(filename, &dialect).unwrap()
+    }
+
+    fn assert_exact(data: &AudioData) {
+        assert_eq!(data.keys(), ["f32", "f64", "i64", "bool"]);
+        for (key, expected) in [
+            ("f32", F32_VALUES.map(|v| Value::F32(v).to_bits())),
+            ("f64", F64_VALUES.map(|v| Value::F64(v).to_bits())),
+        ] {
+            let values = data.get(key).unwrap();
+            let bits: Vec<u64> = (0..values.len())
+                .filter_map(|i| values.get(i))
+                .map(Value::to_bits)
+                .collect();
+            assert_eq!(bits, expected, "{key}");
+        }
+        assert_eq!(
+            data.get("i64").unwrap().as_i64(),
+            Some(&[-3, -2, -1, 0, 1, 2][..])
+        );
+        assert_eq!(
+            data.get("bool").unwrap().as_bool(),
+            Some(&[true, false, true, false, true, false][..])
+        );
+    }
+
+    #[test]
+    fn default_dialect_is_exact() {
+        assert_exact(&round_trip("llad_dialect.csv", CsvDialect::default()));
+    }
+
+    #[test]
+    fn hex_dialect_without_header_is_exact() {
+        let dialect = CsvDialect {
+            delimiter: b'\t',
+            header: false,
+            floats: FloatFormat::Hex,
+            index: IndexColumn::Sample,
+        };
+        assert_exact(&round_trip("llad_dialect_hex.csv", dialect));
+    }
+
+    #[test]
+    fn scientific_dialect_with_time_is_exact() {
+        let dialect = CsvDialect {
+            delimiter: b';',
+            floats: FloatFormat::Scientific(None),
+            index: IndexColumn::Time,
+            ..Default::default()
+        };
+        let data = round_trip("llad_dialect_scientific.csv", dialect);
+        assert_exact(&data);
+        assert_eq!(data.metadata().sample_rate, Some(48000.0));
+    }
+
+    #[test]
+    fn fixed_dialect_rounds_floats() {
+        let dialect = CsvDialect {
+            delimiter: b';',
+            floats: FloatFormat::Fixed(3),
+            ..Default::default()
+        };
+        let data = round_trip("llad_dialect_fixed.csv", dialect);
+
+        let f64_values = data.get("f64").unwrap().as_f64().unwrap();
+        for (value, expected) in f64_values.iter().zip(F64_VALUES) {
+            assert!((value - expected).abs() <= 0.0005 * expected.abs().max(1.0));
+        }
+        assert_eq!(data.get("f32").unwrap().as_f32().unwrap()[0], 0.1);
+        assert_eq!(
+            data.get("i64").unwrap().as_i64(),
+            Some(&[-3, -2, -1, 0, 1, 2][..])
+        );
+    }
+
+    #[test]
+    fn index_column_fields() {
+        assert_eq!(IndexColumn::None.header(Some(48000.0)), None);
+        assert_eq!(IndexColumn::Time.header(None), Some("sample"));
+        assert_eq!(
+            IndexColumn::Time.field(24000, Some(48000.0), FloatFormat::Shortest),
+            Some(String::from("0.5"))
+        );
+        assert_eq!(
+            IndexColumn::Sample.field(7, Some(48000.0), FloatFormat::Hex),
+            Some(String::from("7"))
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use std::{error::Error, fmt, io, sync::Arc};
+
+use crate::{Column, Probe};
+
+/// Every error that LLAD can return. Errors that can occur while logging from the audio thread never allocate: the keys
+/// of columns are shared with the [`crate::SampleLogger`] instead of copied.
+#[derive(Debug)]
+pub enum LladError {
+    /// A value was written to a column that already has a value in the current frame, which would make the columns
+    /// different lengths.
+    Imbalance { column: Arc<str>, sample: u64 },
+    /// A frame was ended while this column has no value in it yet.
+    IncompleteFrame { column: Arc<str>, sample: u64 },
+    /// A value was written to a column that was added after the first frame, so it has no values for earlier frames.
+    ColumnAddedLate { column: Arc<str>, sample: u64 },
+    /// A key was written to that was not registered, while the logger can't add columns anymore because it is
+    /// preallocated or streaming. `column` is the key, which is copied when the error is created.
+    KeyNotRegistered { column: Arc<str> },
+    /// A value was written to a column of a type it can't be converted to, see [`crate::Value`].
+    TypeMismatch { column: Arc<str>, sample: u64 },
+    /// A value outside of the range set with [`crate::SampleLogger::set_range`] was written to a column. The value is
+    /// logged anyway.
+    OutOfRange {
+        column: Arc<str>,
+        sample: u64,
+        value: f64,
+    },
+    /// A [`Column`] handle was used that was not created by this logger.
+    ForeignColumn(Column),
+    /// A [`Probe`] handle was used that was not created by this logger.
+    ForeignProbe(Probe),
+    /// A value was written to a channel that the probe does not have.
+    NoSuchChannel { probe: Arc<str>, channel: usize },
+    /// A column of a logger created with [`crate::SampleLogger::with_capacity`] is full.
+    CapacityExhausted { column: Arc<str>, sample: u64 },
+    /// The space for events reserved with [`crate::SampleLogger::reserve_events`] is used up, so the event at this sample
+    /// was dropped.
+    EventLogFull { sample: u64 },
+    /// The writer thread fell behind and the ring buffer to it was full, so the row of this sample was dropped.
+    FrameDropped { sample: u64 },
+    /// Streaming was started while the logger was already streaming or already had values.
+    StreamingNotAllowed(&'static str),
+    /// A block was started, written to or ended at the wrong time, see [`crate::SampleLogger::begin_block`].
+    BlockNotAllowed(&'static str),
+    /// A value was written to a frame past the end of the current block.
+    OutsideBlock { column: Arc<str>, offset: usize },
+    /// A trigger was set while the logger already had values.
+    TriggerNotAllowed,
+    /// The writer thread panicked before it could write all rows.
+    WriterPanicked,
+    /// The logged data was asked for while it is streamed to disk, so it is not kept in memory.
+    NotInMemory,
+    /// An `.npz` file would be larger than the 4 GiB a zip file can hold without ZIP64 extensions.
+    ArchiveTooLarge,
+    /// A file is not in the LLAD binary format, see [`crate::OutputFormat::Binary`], or in a newer version of it.
+    InvalidBinary(&'static str),
+    /// A column has fewer values than the number of frames to be written to a file in the LLAD binary format, which
+    /// can't hold a frame without a value in every column.
+    ColumnTooShort { column: String, rows: usize },
+    /// A field of a CSV could not be parsed as a value of the type of its column. `row` is the index of the record, not counting the header.
+    Parse {
+        column: Arc<str>,
+        row: usize,
+        value: String,
+    },
+    /// The input file has a channel count that the plugin has no audio IO layout for, and the plugin has no layout
+    /// without a main input either.
+    NoMatchingLayout { channels: usize },
+    /// The plugin has no main output, so there is nothing to write to the output file.
+    NoMainOutput,
+    /// The plugin returned `false` from `Plugin::initialize`.
+    InitializeFailed,
+    /// The plugin returned `ProcessStatus::Error` from `Plugin::process`.
+    Process(&'static str),
+    /// The plugin has no parameter with this ID, see [`crate::ParamProbe::with_ids`].
+    UnknownParameter(String),
+    /// There is no column with this key, see [`crate::write_columns_as_wav`].
+    UnknownColumn(String),
+    /// A WAV file was to be written from columns whose sample rate is not in their [`crate::Metadata`].
+    UnknownSampleRate,
+    /// The command line arguments of [`crate::run_cli`] are invalid.
+    Usage(&'static str),
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// Reading or writing a CSV failed.
+    Csv(csv::Error),
+    /// Reading or writing a WAV file failed.
+    Wav(hound::Error),
+}
+
+impl fmt::Display for LladError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LladError::Imbalance { column, sample } => write!(
+                f,
+                "Element added to column '{column}' at sample {sample} caused imbalance."
+            ),
+            LladError::IncompleteFrame { column, sample } => write!(
+                f,
+                "Frame of sample {sample} was ended without a value for column '{column}'."
+            ),
+            LladError::ColumnAddedLate { column, sample } => write!(
+                f,
+                "Column '{column}' was written to at sample {sample} but was added after the first frame."
+            ),
+            LladError::KeyNotRegistered { column } => {
+                write!(f, "Key '{column}' was not registered up front.")
+            }
+            LladError::TypeMismatch { column, sample } => write!(
+                f,
+                "Value written to column '{column}' at sample {sample} does not match its type."
+            ),
+            LladError::OutOfRange {
+                column,
+                sample,
+                value,
+            } => write!(
+                f,
+                "Value {value} written to column '{column}' at sample {sample} is out of range."
+            ),
+            LladError::ForeignColumn(column) => {
+                write!(f, "{column:?} does not belong to this logger.")
+            }
+            LladError::ForeignProbe(probe) => {
+                write!(f, "{probe:?} does not belong to this logger.")
+            }
+            LladError::NoSuchChannel { probe, channel } => {
+                write!(f, "Probe '{probe}' has no channel {channel}.")
+            }
+            LladError::CapacityExhausted { column, sample } => write!(
+                f,
+                "Preallocated capacity of column '{column}' exhausted at sample {sample}."
+            ),
+            LladError::EventLogFull { sample } => {
+                write!(f, "Event log is full, event at sample {sample} was dropped.")
+            }
+            LladError::FrameDropped { sample } => write!(
+                f,
+                "Ring buffer to writer thread is full, row of sample {sample} was dropped."
+            ),
+            LladError::StreamingNotAllowed(reason) => write!(f, "Can't start streaming: {reason}"),
+            LladError::BlockNotAllowed(reason) => write!(f, "Block not allowed: {reason}"),
+            LladError::OutsideBlock { column, offset } => write!(
+                f,
+                "Value written to column '{column}' at offset {offset} is outside of the current block."
+            ),
+            LladError::TriggerNotAllowed => {
+                write!(f, "Can't set a trigger after values have been written.")
+            }
+            LladError::WriterPanicked => write!(f, "Writer thread panicked."),
+            LladError::NotInMemory => {
+                write!(f, "Logged data is streamed to disk and not kept in memory.")
+            }
+            LladError::ArchiveTooLarge => {
+                write!(f, "Archive exceeds the 4 GiB a zip file can hold.")
+            }
+            LladError::InvalidBinary(reason) => {
+                write!(f, "Not a valid LLAD binary file: {reason}")
+            }
+            LladError::ColumnTooShort { column, rows } => {
+                write!(f, "Column '{column}' has fewer than {rows} values.")
+            }
+            LladError::Parse { column, row, value } => write!(
+                f,
+                "Could not parse '{value}' in column '{column}' at row {row}."
+            ),
+            LladError::NoMatchingLayout { channels } => write!(
+                f,
+                "Plugin has no audio IO layout with {channels} input channels or without a main input."
+            ),
+            LladError::NoMainOutput => {
+                write!(f, "Plugins without a main output are not supported.")
+            }
+            LladError::InitializeFailed => write!(f, "Plugin failed to initialize."),
+            LladError::Process(message) => write!(f, "Plugin failed to process: {message}"),
+            LladError::UnknownParameter(id) => write!(f, "Plugin has no parameter '{id}'."),
+            LladError::UnknownColumn(key) => write!(f, "There is no column '{key}'."),
+            LladError::UnknownSampleRate => write!(f, "The sample rate of the columns is not known."),
+            LladError::Usage(usage) => write!(f, "{usage}"),
+            LladError::Io(error) => write!(f, "{error}"),
+            LladError::Csv(error) => write!(f, "{error}"),
+            LladError::Wav(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for LladError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LladError::Io(error) => Some(error),
+            LladError::Csv(error) => Some(error),
+            LladError::Wav(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LladError {
+    fn from(error: io::Error) -> Self {
+        LladError::Io(error)
+    }
+}
+
+impl From<csv::Error> for LladError {
+    fn from(error: csv::Error) -> Self {
+        LladError::Csv(error)
+    }
+}
+
+impl From<hound::Error> for LladError {
+    fn from(error: hound::Error) -> Self {
+        LladError::Wav(error)
+    }
+}
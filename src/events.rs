@@ -0,0 +1,296 @@
+use std::{borrow::Cow, fs::File, sync::Arc};
+
+use crate::{value::format_value, ColumnType, FloatFormat, LladError, Value};
+
+/// The maximum number of fields in a [`Payload`].
+pub const MAX_PAYLOAD_FIELDS: usize = 6;
+
+/// The named values attached to an event, e.g. the note and velocity of a note on. A payload has a fixed size so that
+/// logging an event never allocates, it holds at most [`MAX_PAYLOAD_FIELDS`] fields.
+///
+/// # Fields
+///
+/// * `fields`: The name and value of every field, only the first `len` are used.
+/// * `len`: The number of fields.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Payload {
+    fields: [(&'static str, Value); MAX_PAYLOAD_FIELDS],
+    len: usize,
+}
+
+impl Default for Payload {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Payload {
+    /// Creates a payload without any fields.
+    ///
+    /// # Returns
+    ///
+    /// * `Payload`: The newly created, empty payload.
+    pub fn new() -> Self {
+        Self {
+            fields: [("", Value::F32(0.0)); MAX_PAYLOAD_FIELDS],
+            len: 0,
+        }
+    }
+
+    /// Adds a field to the payload. Fields beyond [`MAX_PAYLOAD_FIELDS`] are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the field.
+    /// * `value`: The value of the field.
+    ///
+    /// # Returns
+    ///
+    /// * `Payload`: The payload with the field added.
+    pub fn with(mut self, name: &'static str, value: impl Into<Value>) -> Self {
+        if self.len < MAX_PAYLOAD_FIELDS {
+            self.fields[self.len] = (name, value.into());
+            self.len += 1;
+        }
+
+        self
+    }
+
+    /// Returns the name and value of every field, in the order in which they were added.
+    pub fn fields(&self) -> &[(&'static str, Value)] {
+        &self.fields[..self.len]
+    }
+
+    /// Formats the payload for the event log, as `name:type=value` pairs separated by `;`, e.g. `velocity:f32=1`. The
+    /// type is the name of the type as in the sidecar file, so that every value reads back as the type it was logged
+    /// with.
+    fn format(&self) -> String {
+        self.fields()
+            .iter()
+            .map(|&(name, value)| {
+                let column_type = value.column_type();
+                let value = format_value(&[], value, FloatFormat::Shortest);
+                format!("{name}:{}={value}", column_type.name())
+            })
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+}
+
+/// An event logged by [`crate::SampleLogger::event`].
+///
+/// # Fields
+///
+/// * `sample`: The index of the frame in which the event happened.
+/// * `label`: What happened.
+/// * `payload`: The values attached to the event.
+pub(crate) struct Event {
+    pub sample: u64,
+    pub label: Cow<'static, str>,
+    pub payload: Payload,
+}
+
+/// An event as read back by [`read_events`].
+///
+/// # Fields
+///
+/// * `sample`: The index of the frame in which the event happened, which is the row in the CSV of a logger without a
+///   trigger.
+/// * `label`: What happened.
+/// * `payload`: The name and value of every field of the payload, as the type they were logged with. Values in event
+///   logs without types are read as [`Value::I64`] if they are integers, [`Value::Bool`] if they are `true` or `false`
+///   and [`Value::F64`] otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRecord {
+    pub sample: u64,
+    pub label: String,
+    pub payload: Vec<(String, Value)>,
+}
+
+/// Returns the name of the event log of a CSV.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the CSV.
+///
+/// # Returns
+///
+/// * `String`: The name of the event log, the name of the CSV with `.events.csv` appended.
+pub(crate) fn events_file(filename: &str) -> String {
+    format!("{filename}.events.csv")
+}
+
+/// Writes the event log of a CSV, a CSV with the columns `sample`, `label` and `payload`.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the CSV.
+/// * `events`: The events, in the order in which they were logged.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the event log is written, or the error while writing it.
+pub(crate) fn write_events(filename: &str, events: &[Event]) -> Result<(), LladError> {
+    let mut writer = csv::Writer::from_writer(File::create(events_file(filename))?);
+    writer.write_record(["sample", "label", "payload"])?;
+
+    for event in events {
+        writer.write_record([
+            event.sample.to_string().as_str(),
+            &event.label,
+            event.payload.format().as_str(),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the event log that [`crate::SampleLogger`] writes next to a CSV, so events can be overlaid on the plots of
+/// the columns read by [`crate::read_csv_as_audio_data`].
+///
+/// # Arguments
+///
+/// * `filename` - A string representing the path to the CSV file, not to the event log itself.
+///
+/// # Returns
+///
+/// * `Result<Vec<EventRecord>, LladError>` - On success, returns every event in the order in which they were logged,
+///   which is empty if the CSV has no event log because no events were logged. On failure, returns an error.
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * The event log exists but cannot be opened, as [`LladError::Io`].
+/// * There is an error reading the records, as [`LladError::Csv`].
+/// * A sample or payload can't be parsed, as [`LladError::Parse`] with the column, row and field.
+pub fn read_events(filename: String) -> Result<Vec<EventRecord>, LladError> {
+    let file = match File::open(events_file(filename.as_str())) {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut reader = csv::Reader::from_reader(file);
+    let mut events = Vec::new();
+
+    for (row, record) in reader.records().enumerate() {
+        let record = record?;
+        let field = |index: usize| record.get(index).unwrap_or_default();
+        let parse_error = |column: &str, value: &str| LladError::Parse {
+            column: Arc::from(column),
+            row,
+            value: String::from(value),
+        };
+
+        let sample = field(0)
+            .parse()
+            .map_err(|_| parse_error("sample", field(0)))?;
+
+        let mut payload = Vec::new();
+        for pair in field(2).split(';').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or_else(|| parse_error("payload", pair))?;
+            let (name, value) =
+                parse_payload_field(name, value).ok_or_else(|| parse_error("payload", pair))?;
+            payload.push((String::from(name), value));
+        }
+
+        events.push(EventRecord {
+            sample,
+            label: String::from(field(1)),
+            payload,
+        });
+    }
+
+    Ok(events)
+}
+
+/// Parses a field of a payload, see [`Payload::format`].
+///
+/// # Arguments
+///
+/// * `name`: The part before the `=`, the name of the field and its type if it has one.
+/// * `value`: The part after the `=`.
+///
+/// # Returns
+///
+/// * `Option<(&str, Value)>`: The name and value of the field, or `None` if the value is not of its type.
+fn parse_payload_field<'a>(name: &'a str, value: &str) -> Option<(&'a str, Value)> {
+    let typed = name.rsplit_once(':').and_then(|(field, type_name)| {
+        ColumnType::from_name(type_name, Vec::new()).map(|column_type| (field, column_type))
+    });
+
+    match typed {
+        Some((name, column_type)) => Some((name, column_type.parse(value)?)),
+        None => Some((name, parse_untyped(value)?)),
+    }
+}
+
+/// Parses the value of a field of a payload that was written without its type, see [`EventRecord`].
+fn parse_untyped(value: &str) -> Option<Value> {
+    if let Ok(value) = value.parse() {
+        return Some(Value::I64(value));
+    }
+
+    match value {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => value.parse().ok().map(Value::F64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SampleLogger;
+
+    #[test]
+    fn payload_values_keep_their_type() {
+        let filename = std::env::temp_dir().join("llad_events.csv");
+        let filename = filename.to_string_lossy().into_owned();
+        let payload = Payload::new()
+            .with("velocity", 1.0_f32)
+            .with("gain", 0.1_f64)
+            .with("note", 60)
+            .with("on", true);
+
+        let mut logger = SampleLogger::new(filename.clone());
+        logger.write("input", 0.0_f32).unwrap();
+        logger.event("note_on", payload).unwrap();
+        logger.end_frame().unwrap();
+        logger.write_debug_values().unwrap();
+
+        let events = read_events(filename).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].label, "note_on");
+        assert_eq!(
+            events[0].payload,
+            vec![
+                (String::from("velocity"), Value::F32(1.0)),
+                (String::from("gain"), Value::F64(0.1)),
+                (String::from("note"), Value::I64(60)),
+                (String::from("on"), Value::Bool(true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn untyped_payload_values_are_inferred() {
+        assert_eq!(
+            parse_payload_field("note", "60"),
+            Some(("note", Value::I64(60)))
+        );
+        assert_eq!(
+            parse_payload_field("gain", "0.5"),
+            Some(("gain", Value::F64(0.5)))
+        );
+        assert_eq!(
+            parse_payload_field("a:b", "true"),
+            Some(("a:b", Value::Bool(true)))
+        );
+        assert_eq!(parse_payload_field("note:i64", "0.5"), None);
+    }
+}
@@ -2,238 +2,86 @@
 //! every sample time. The CSV can then be plotted in a spreadshot program or with e.g. matplotlib.
 //! This crate has an `disable` feature which when turned on disables all the code that might slow
 //! down the plugin. This way it's possible to insert the logging in a plugin but build it in a
-//! production mode as well where the logging doesn't cause performance issues.
+//! production mode as well where the logging doesn't cause performance issues. The logging macros, such as
+//! [`llad_log!`], go further: with the feature on they don't evaluate their arguments at all, and the [`DebugLogger`]
+//! field they log to takes no space.
+//!
+//! The columns of a CSV written by [`SampleLogger`] are in the order in which they were registered or first written
+//! to, unless an explicit order is given with [`SampleLogger::set_column_order`]. [`read_csv_as_audio_data`] keeps that
+//! order in the returned [`AudioData`]. Columns can hold floats, `f64`, `i64`, `bool` or enum values, see
+//! [`ColumnType`], which are read back in their type through a sidecar file next to the CSV. Sparse events, such as note
+//! ons or parameter changes, go to a separate event log that [`read_events`] reads back. The sidecar file also holds
+//! [`Metadata`] such as the sample rate, from which [`AudioData::time_axis`] gives the time of every row in seconds.
+//!
+//! To run a plugin without a host, [`Runner`] drives any nih-plug `Plugin` over a WAV file and flushes its
+//! [`SampleLogger`] at the end. [`run_cli`] wraps it into a command line tool. Wrapping the plugin in [`Probed`] logs its
+//! input, output and the note events it receives without any code in the plugin itself, and [`ParamProbe`] logs the
+//! values of its parameters.
+//!
+//! To capture a short window around an event deep into a session, [`SampleLogger::set_trigger`] arms a [`Trigger`]
+//! that keeps a configurable number of frames from before the event. [`SampleLogger::set_watchdog`] catches NaN,
+//! infinite and denormal values, and can stop logging or fire the trigger where they first appear. Invariants of a
+//! column are declared with [`SampleLogger::set_range`], and every value that violates them is reported.
+//!
+//! Besides the CSV, [`write_vcd`] writes the logged columns as a Value Change Dump, to be browsed in a waveform viewer
+//! such as GTKWave, with the dotted keys of the columns as scopes. [`read_vcd`] reads it back. For analysis in Python,
+//! [`write_npz`] writes them as NumPy arrays with their exact values. Long captures are smaller and exact in the
+//! binary format of [`OutputFormat::Binary`], which [`BinaryReader`] streams frame by frame and [`binary_to_csv`]
+//! converts back to a CSV. To listen to an internal signal instead, [`write_columns_as_wav`] writes columns as the
+//! channels of a WAV file.
+//!
+//! Floats are written to the CSV in the shortest notation that reads back exactly. [`SampleLogger::set_csv_dialect`]
+//! changes the delimiter, writes them with a fixed precision, in scientific notation or as exact hexadecimal floats,
+//! and adds an index column with the sample or time of every row. [`read_csv_with_dialect`] reads such a CSV back.
 
 extern crate csv;
-use std::{collections::HashMap, error::Error, fs::File};
 
-/// Reads a CSV file and converts it into a hashmap where each key corresponds to a column header
-/// and each value is a vector of floats representing the audio data. This function exists to
-/// allow you to write your own plotters/handlers of the data written to the output of this library,
-/// so in a sense this function is the inverse of the result that [`SampleLogger`] produces.
-///
-/// # Arguments
-///
-/// * `filename` - A string representing the path to the CSV file.
-///
-/// # Returns
-///
-/// * `Result<HashMap<String, Vec<f32>>, Box<dyn Error>>` - On success, returns a HashMap where each
-///   key is a column header from the CSV and each value is a vector of floats representing the audio
-///   data in that column. On failure, returns an error.
-///
-/// # Errors
-///
-/// This function will return an error if:
-///
-/// * The file cannot be opened.
-/// * There is an error reading the CSV headers or records.
-/// * There is a parse error while reading CSV data.
-///
-/// # Panics
-///
-/// This function might panic if a parse error occurs while reading CSV data.
-pub fn read_csv_as_audio_data(
-    filename: String,
-) -> Result<HashMap<String, Vec<f32>>, Box<dyn Error>> {
-    let mut reader = csv::Reader::from_reader(File::open(filename.as_str())?);
-    let headers = reader.headers()?.clone();
-    let mut data = HashMap::new();
-
-    for record in reader.records() {
-        let record = record?;
-        let row: Vec<f32> = record
-            .iter()
-            .map(|x| x.parse().expect("parse error on reading csv"))
-            .collect();
-
-        for (i, header) in headers.iter().enumerate() {
-            data.entry(String::from(header))
-                .or_insert(Vec::new())
-                .push(row[i]);
-        }
-    }
-
-    Ok(data)
-}
-
-/// Logs the operation of an audio plugin to a CSV file. Every line of this CSV is either a sample, or some other value
-/// during operation of the plugin at exactly that time. Enforces that every column of the CSV is the same length.
-///
-/// For example, one column could be the sample before processing, and another after processing. Additionally columns could be
-/// filled with (if applicable) the envelope at that sample, attack/release information, the absolute value of a sample, the
-/// current gain parameter, or whatever else might be interesting to look at in detail.
-///
-/// It is mandatory to have at least one field named 'sample', since that's what's counted to determine that the logging should
-/// stop.
-///
-/// A minimal structure to use this library in a VST3 plugin (e.g. using [nih-plug](https://github.com/robbert-vdh/nih-plug))
-/// requires:
-///
-/// * A [`SampleLogger`] (likely on the struct that implements `Plugin`).
-/// * For every sample that the plugin handles, calls to [`SampleLogger::write`], at least one of which must be named 'sample'.
-/// * on deactivation of the plugin, or termination of the program, a call to [`SampleLogger::write_debug_values`].
-///
-/// A project using this crate can be found [here](https://github.com/PietPtr/compressor).
-///
-/// # Fields
-///
-/// * `debug_values`: A map where each key corresponds to an identifier and each value is a vector of floats representing the data.
-/// * `samples_seen`: A counter for the number of samples seen.
-/// * `quit_after_n_samples`: An optional field specifying the number of samples after which logging should stop.
-/// * `output_file`: The name of the file where the logged data will be written to.
-pub struct SampleLogger {
-    debug_values: HashMap<String, Vec<f32>>,
-    samples_seen: u64,
-    quit_after_n_samples: Option<u64>,
-    output_file: String,
-}
-
-impl SampleLogger {
-    /// Creates a new `SampleLogger` instance with sensible defaults.
-    ///
-    /// # Arguments
-    ///
-    /// * `output_file`: The name of the file where the logged data will be written to.
-    ///
-    /// # Returns
-    ///
-    /// * `SampleLogger`: The newly created `SampleLogger` instance.
-    pub fn new(output_file: String) -> Self {
-        Self {
-            debug_values: HashMap::new(),
-            samples_seen: 0,
-            quit_after_n_samples: None,
-            output_file,
-        }
-    }
-
-    /// Logs a single sample or skips if enough samples have been logged. Also applies the enforcement of every
-    /// column having the same length.
-    ///
-    /// # Arguments
-    ///
-    /// * `key`: A string slice representing the identifier of the stream of samples that this sample should be written to.
-    /// * `value`: A float representing the sample value.
-    ///
-    /// # Returns
-    ///
-    /// * `Result<(), &'static str>`: Returns `Ok(())` if the sample is logged successfully or if logging is disabled.
-    ///   Returns `Err` with a static string describing the error otherwise.
-    pub fn write(&mut self, key: &str, value: f32) -> Result<(), &'static str> {
-        if !cfg!(feature = "disabled") {
-            if !self.is_logging_active() {
-                return Ok(()); // Don't write anything if we've seen enough samples.
-            }
-
-            self.debug_values
-                .entry(String::from(key))
-                .or_insert(Vec::new())
-                .push(value);
-
-            if key == "sample" {
-                // TODO: Could count all values, since after one iteration the amount of keys is known, and would remove the mandatory 'sample' key
-                self.samples_seen += 1;
-            }
-
-            return self.is_logged_correctly();
-        } else {
-            Ok(())
-        }
-    }
-
-    /// Sets the `quit_after_n_samples` field. Useful if the plugin is applied to a large audio file and you'd like the
-    /// CSV to remain tiny.
-    ///
-    /// # Arguments
-    ///
-    /// * `samples`: A float representing the number of samples after which logging should stop.
-    pub fn set_quit_after_n_samples(&mut self, samples: u64) {
-        self.quit_after_n_samples = Some(samples);
-    }
-
-    /// Determines whether the logging is still active based on the number of samples seen and
-    /// the optional `quit_after_n_samples` field. Returns true if quit_after_n_samples is None.
-    ///
-    /// # Returns
-    ///
-    /// * `bool`: Returns `true` if logging is still active (i.e., the number of samples seen is
-    ///   less than the optional `quit_after_n_samples` field or if `quit_after_n_samples` is `None`).
-    ///   Returns `false` otherwise.
-    pub fn is_logging_active(&self) -> bool {
-        match self.quit_after_n_samples {
-            Some(limit) => self.samples_seen < limit,
-            None => true,
-        }
-    }
-
-    /// Checks whether the logged data is correctly formatted, this function enforces that the 'sample' key is present
-    /// and that every column stays of the same size.
-    ///
-    /// # Returns
-    ///
-    /// * `Result<(), &'static str>`: Returns `Ok(())` if the logged data is correctly formatted.
-    ///   Returns `Err` either when an element causes the columns to not be the same length anymore
-    ///   or when the 'sample' key is not present.
-    fn is_logged_correctly(&self) -> Result<(), &'static str> {
-        let lengths: Vec<usize> = self
-            .debug_values
-            .values()
-            .map(|vec| vec.len())
-            .collect();
-
-            
-        // Checks whether all lists have n or n+1 elements.
-        let n = if !lengths.is_empty() {
-            lengths.iter().sum::<usize>() / lengths.len()
-        } else {
-            0
-        };
-
-        if !lengths.iter().all(|&elem| elem == n || elem == n + 1) {
-            return Err("Element added to list caused imbalance.");
-        }
-
-        if n > 1 && !self.debug_values.keys().any(|elem| elem.eq("sample")) {
-            dbg!(self.debug_values.keys());
-            return Err("First sample iteration has been added but no key 'sample' is present.");
-        }
-
-        Ok(())
-    }
-
-    /// Writes the logged data to the specified output file. Call this on shutdown of the plugin.
-    ///
-    /// # Returns
-    ///
-    /// * `Result<(), Box<dyn Error>>`: Returns `Ok(())` if the data is written successfully or if logging is disabled.
-    ///    Propagates any IO errors otherswise.
-    pub fn write_debug_values(&mut self) -> Result<(), Box<dyn Error>> {
-        if !cfg!(feature = "disabled") {
-            self.is_logged_correctly()?;
-
-            let max_len = self
-                .debug_values
-                .values()
-                .map(|v| v.len())
-                .max()
-                .unwrap_or(0);
-
-            let file = File::create(self.output_file.as_str())?;
-            let mut writer = csv::Writer::from_writer(file);
-
-            writer.write_record(self.debug_values.keys())?;
-
-            for i in 0..max_len {
-                let mut record = csv::StringRecord::new();
-                for value in self.debug_values.values() {
-                    let entry = value.get(i).map(|v| v.to_string()).unwrap_or(String::new());
-                    record.push_field(entry.as_str());
-                }
-                writer.write_record(&record)?;
-            }
-        }
-
-        Ok(())
-    }
-}
+mod audio_data;
+mod binary;
+mod block;
+mod buffers;
+mod config;
+mod dialect;
+mod error;
+mod events;
+mod logger;
+mod macros;
+mod meta;
+mod notes;
+mod npy;
+mod panic;
+mod params;
+mod probed;
+mod range;
+mod ring;
+mod runner;
+mod stream;
+mod trigger;
+mod value;
+mod vcd;
+mod watchdog;
+mod wav;
+
+pub use audio_data::{read_csv_as_audio_data, read_csv_with_dialect, AudioData};
+pub use binary::{
+    binary_to_csv, csv_to_binary, read_binary_as_audio_data, write_binary, BinaryReader,
+    OutputFormat,
+};
+pub use dialect::{CsvDialect, FloatFormat, IndexColumn};
+pub use error::LladError;
+pub use events::{read_events, EventRecord, Payload, MAX_PAYLOAD_FIELDS};
+pub use logger::{Column, Probe, SampleLogger};
+#[doc(hidden)]
+pub use macros::LogKey;
+pub use macros::{DebugLogger, DisabledLogger};
+pub use meta::Metadata;
+pub use npy::{write_npy, write_npz};
+pub use params::ParamProbe;
+pub use probed::Probed;
+pub use range::{read_violations, Violation};
+pub use runner::{run_cli, Logged, Runner};
+pub use trigger::{Edge, Trigger};
+pub use value::{ColumnData, ColumnType, Value};
+pub use vcd::{read_vcd, write_vcd};
+pub use watchdog::{Anomaly, AnomalyKind, WatchdogAction};
+pub use wav::write_columns_as_wav;
@@ -0,0 +1,1642 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    ops::RangeBounds,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    audio_data::{self, channel_key},
+    binary,
+    block::Block,
+    buffers::{self, BufferPosition},
+    events::{self, Event},
+    meta, panic,
+    range::{self, Range, Recorded, Violation},
+    stream::Stream,
+    trigger::{Capture, FrameEnd},
+    watchdog::{Anomaly, Watchdog, WatchdogAction},
+    AudioData, ColumnData, ColumnType, CsvDialect, LladError, Metadata, OutputFormat, Payload,
+    Trigger, Value,
+};
+
+/// A handle to a column of a [`SampleLogger`], obtained through [`SampleLogger::register`]. Writing through a handle
+/// with [`SampleLogger::write_column`] is an array store instead of a hash lookup of the column's key, so register all
+/// columns in e.g. `Plugin::initialize` and keep the handles around for use in `Plugin::process`.
+///
+/// A handle is only meaningful for the logger that created it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Column(usize);
+
+/// A handle to a multichannel probe of a [`SampleLogger`], obtained through [`SampleLogger::register_probe`]. A probe
+/// is a column per channel, named `probe[0]`, `probe[1]` and so on, which [`AudioData::channels`] groups again.
+///
+/// A handle is only meaningful for the logger that created it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Probe(usize);
+
+/// Logs the operation of an audio plugin to a CSV file. Every line of this CSV is a frame: the values of all columns
+/// during operation of the plugin at exactly one sample time. Enforces that every column of the CSV is the same length.
+///
+/// For example, one column could be the sample before processing, and another after processing. Additionally columns could be
+/// filled with (if applicable) the envelope at that sample, attack/release information, the absolute value of a sample, the
+/// current gain parameter, or whatever else might be interesting to look at in detail.
+///
+/// Columns hold floats by default, but can also hold `f64`, `i64`, `bool` or enum values, see [`ColumnType`]. The type
+/// of every column is written to a sidecar file next to the CSV, from which [`crate::read_csv_as_audio_data`] reads
+/// the columns back in their type.
+///
+/// Things that happen at a single sample rather than every sample, like a note on or a parameter change, are logged
+/// with [`SampleLogger::event`] to an event log next to the CSV, which [`crate::read_events`] reads back.
+///
+/// The sample rate, buffer size and plugin are written to the sidecar file as [`crate::Metadata`] when they are set with
+/// [`SampleLogger::set_buffer_config`] in `Plugin::initialize`, so that the CSV can be plotted against time.
+///
+/// Filters that blow up to NaN or decay into denormals are caught by [`SampleLogger::set_watchdog`], which records the
+/// first frame in which every column got such a value.
+///
+/// Invariants such as "the gain reduction is in -60..=0" are declared with [`SampleLogger::set_range`], after which
+/// every value outside of the range is reported in a file next to the CSV, which [`crate::read_violations`] reads back.
+///
+/// Instead of a CSV, [`SampleLogger::set_output_format`] can select a compact binary format that keeps every value
+/// exactly, see [`OutputFormat::Binary`].
+///
+/// To see where every call to `Plugin::process` began, [`SampleLogger::log_buffers`] adds columns with the buffer
+/// index, the offset in the buffer and the buffer size of every frame.
+///
+/// Stereo or surround signals are logged through a [`Probe`], which stores every channel in its own column named
+/// `probe[channel]` so that [`AudioData::channels`] can group them again.
+///
+/// Frames are delimited explicitly with [`SampleLogger::end_frame`], which is also what's counted to determine that the
+/// logging should stop. Every column must get exactly one value per frame. The columns are the ones written to in the
+/// first frame, plus any that were registered before it.
+///
+/// A minimal structure to use this library in a VST3 plugin (e.g. using [nih-plug](https://github.com/robbert-vdh/nih-plug))
+/// requires:
+///
+/// * A [`SampleLogger`] (likely on the struct that implements `Plugin`). When nih-plug's `assert_process_allocs` feature
+///   is enabled, create it with [`SampleLogger::with_capacity`] so that logging never allocates on the audio thread.
+/// * For every sample that the plugin handles, calls to [`SampleLogger::write`] followed by a call to
+///   [`SampleLogger::end_frame`]. Alternatively, [`SampleLogger::register`] the columns up front and write to them with
+///   [`SampleLogger::write_column`].
+/// * on deactivation of the plugin, or termination of the program, a call to [`SampleLogger::write_debug_values`].
+///   A logger that is dropped without it writes the logged data anyway, see [`SampleLogger::install_panic_hook`] for
+///   when the plugin panics.
+///
+/// By default all logged data is kept in memory until [`SampleLogger::write_debug_values`] is called. For long sessions,
+/// [`SampleLogger::start_streaming`] instead hands every row to a background thread that writes it to disk right away.
+/// To only capture the frames around an event, arm a [`Trigger`] with [`SampleLogger::set_trigger`].
+///
+/// A project using this crate can be found [here](https://github.com/PietPtr/compressor).
+///
+/// # Fields
+///
+/// * `debug_values`: The key and logged values of every column, indexed by [`Column`] and in registration order.
+/// * `names`: The key of every column, indexed by [`Column`], shared with errors so that creating them never allocates.
+/// * `column_indices`: A map from the key of a column to its [`Column`] handle.
+/// * `probe_names`: The key of every probe, indexed by [`Probe`].
+/// * `probe_columns`: The column of every channel of every probe, indexed by [`Probe`] and channel.
+/// * `column_order`: Keys of columns that are written to the CSV before all other columns, in this order.
+/// * `in_frame`: Whether every column already has a value in the current frame, indexed by [`Column`].
+/// * `columns_in_frame`: The number of columns that already have a value in the current frame.
+/// * `samples_seen`: A counter for the number of frames that have been ended.
+/// * `stored`: The number of ended frames that are held in the columns in memory.
+/// * `cursor`: The index in every column where the value of the current frame is stored. Equal to `stored`, except
+///   while the columns are used as the pre-trigger ring buffer of a trigger that has not fired yet.
+/// * `quit_after_n_samples`: An optional field specifying the number of samples after which logging should stop.
+/// * `output_file`: The name of the file where the logged data will be written to.
+/// * `preallocated`: Whether the columns were allocated up front by [`SampleLogger::with_capacity`], in which case
+///   [`SampleLogger::write`] never allocates and errors instead of growing.
+/// * `stream`: The background writer that rows are handed to, if streaming has been started.
+/// * `capture`: The state of the trigger, if one has been set.
+/// * `block`: The frames of the current block, see [`SampleLogger::begin_block`].
+/// * `events`: The events that have been logged, in order.
+/// * `buffer_columns`: The index, offset and size columns, if buffers are logged, see [`SampleLogger::log_buffers`].
+/// * `buffer`: The position of the current frame in the buffers passed to `Plugin::process`.
+/// * `watchdog`: The state of the watchdog, if it is enabled.
+/// * `ranges`: The range of values of every column, if one is set, indexed by [`Column`].
+/// * `violations`: The values that were outside of the range of their column, in order.
+/// * `range_errors`: Whether a value outside of the range of its column is returned as an error.
+/// * `flushed`: Whether [`SampleLogger::write_debug_values`] has been called, otherwise the logger writes on drop.
+/// * `output_format`: The format of the output file.
+/// * `csv_dialect`: The dialect of the output file if it is a CSV.
+pub struct SampleLogger {
+    debug_values: AudioData,
+    names: Vec<Arc<str>>,
+    column_indices: HashMap<Arc<str>, Column>,
+    probe_names: Vec<Arc<str>>,
+    probe_columns: Vec<Vec<Column>>,
+    column_order: Vec<String>,
+    in_frame: Vec<bool>,
+    columns_in_frame: usize,
+    samples_seen: u64,
+    stored: usize,
+    cursor: usize,
+    quit_after_n_samples: Option<u64>,
+    output_file: String,
+    preallocated: bool,
+    stream: Option<Stream>,
+    capture: Option<Capture>,
+    block: Block,
+    events: Vec<Event>,
+    buffer_columns: Option<[Column; 3]>,
+    buffer: BufferPosition,
+    watchdog: Option<Watchdog>,
+    ranges: Vec<Option<Range>>,
+    violations: Vec<Recorded>,
+    range_errors: bool,
+    flushed: bool,
+    output_format: OutputFormat,
+    csv_dialect: CsvDialect,
+}
+
+impl SampleLogger {
+    /// Creates a new `SampleLogger` instance with sensible defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_file`: The name of the file where the logged data will be written to.
+    ///
+    /// # Returns
+    ///
+    /// * `SampleLogger`: The newly created `SampleLogger` instance.
+    pub fn new(output_file: String) -> Self {
+        let mut debug_values = AudioData::new();
+        debug_values.metadata.timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|time| time.as_secs());
+
+        Self {
+            debug_values,
+            names: Vec::new(),
+            column_indices: HashMap::new(),
+            probe_names: Vec::new(),
+            probe_columns: Vec::new(),
+            column_order: Vec::new(),
+            in_frame: Vec::new(),
+            columns_in_frame: 0,
+            samples_seen: 0,
+            stored: 0,
+            cursor: 0,
+            quit_after_n_samples: None,
+            output_file,
+            preallocated: false,
+            stream: None,
+            capture: None,
+            block: Block::default(),
+            events: Vec::new(),
+            buffer_columns: None,
+            buffer: BufferPosition::default(),
+            watchdog: None,
+            ranges: Vec::new(),
+            violations: Vec::new(),
+            range_errors: false,
+            flushed: false,
+            output_format: OutputFormat::Csv,
+            csv_dialect: CsvDialect::default(),
+        }
+    }
+
+    /// Creates a new `SampleLogger` instance which allocates all memory it will ever need up front. Calls to
+    /// [`SampleLogger::write`] on such a logger never allocate, which makes it safe to use from `Plugin::process` with
+    /// nih-plug's `assert_process_allocs` feature enabled. Call this in e.g. `Plugin::initialize`, not on the audio thread.
+    ///
+    /// Writing to a key that is not in `columns`, or writing more than `samples` values to a column, results in an
+    /// error instead of a reallocation. `quit_after_n_samples` is set to `samples`.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_file`: The name of the file where the logged data will be written to.
+    /// * `columns`: The keys of all columns that will be written to.
+    /// * `samples`: The number of samples to reserve space for in every column.
+    ///
+    /// # Returns
+    ///
+    /// * `SampleLogger`: The newly created `SampleLogger` instance.
+    pub fn with_capacity(output_file: String, columns: &[&str], samples: u64) -> Self {
+        let mut logger = Self::new(output_file);
+        logger.quit_after_n_samples = Some(samples);
+        logger.preallocated = true;
+
+        for key in columns {
+            logger.register(key);
+        }
+
+        logger
+    }
+
+    /// Registers a float column and returns a handle to it, or returns the existing handle if a column with this key was
+    /// registered before. For a logger created with [`SampleLogger::with_capacity`], the new column reserves the same
+    /// amount of space as the other columns. This allocates, so call it before processing starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The identifier of the column, which is used as its header in the CSV.
+    ///
+    /// # Returns
+    ///
+    /// * `Column`: The handle to pass to [`SampleLogger::write_column`].
+    pub fn register(&mut self, key: &str) -> Column {
+        self.register_typed(key, ColumnType::F32)
+    }
+
+    /// Registers a column of the given type and returns a handle to it, or returns the existing handle if a column with
+    /// this key was registered before, in which case its type is not changed. See [`SampleLogger::register`].
+    ///
+    /// A column registered while a block is active, see [`SampleLogger::begin_block`], is added to the block, so that
+    /// a plugin run inside a block, e.g. by [`crate::Probed`], can still create its columns with
+    /// [`SampleLogger::write`] in the first block.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The identifier of the column, which is used as its header in the CSV.
+    /// * `column_type`: The type of the values of the column.
+    ///
+    /// # Returns
+    ///
+    /// * `Column`: The handle to pass to [`SampleLogger::write_column`].
+    pub fn register_typed(&mut self, key: &str, column_type: ColumnType) -> Column {
+        if let Some(&column) = self.column_indices.get(key) {
+            return column;
+        }
+
+        let capacity = match &self.capture {
+            Some(capture) => capture.frames(),
+            None if self.preallocated => self.quit_after_n_samples.unwrap_or(0) as usize,
+            None => 0,
+        };
+
+        let column = Column(self.debug_values.len());
+        let name: Arc<str> = Arc::from(key);
+        self.debug_values.push_column(
+            String::from(key),
+            ColumnData::with_capacity(column_type, capacity),
+        );
+        self.names.push(name.clone());
+        self.column_indices.insert(name, column);
+        self.in_frame.push(false);
+        self.ranges.push(None);
+        if let Some(watchdog) = &mut self.watchdog {
+            watchdog.first.push(None);
+        }
+        if self.block.active {
+            self.block.widen(self.names.len());
+        }
+
+        column
+    }
+
+    /// Registers a multichannel probe, which is a float column per channel named `key[channel]`, and returns a handle to
+    /// it. Returns the existing handle if a probe with this key was registered before, in which case its number of
+    /// channels is not changed. This allocates, so call it before processing starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The identifier of the probe, from which the headers of its columns in the CSV are derived.
+    /// * `channels`: The number of channels of the probe.
+    ///
+    /// # Returns
+    ///
+    /// * `Probe`: The handle to pass to [`SampleLogger::write_probe`] and [`SampleLogger::write_channels`].
+    pub fn register_probe(&mut self, key: &str, channels: usize) -> Probe {
+        if let Some(index) = self.probe_names.iter().position(|name| &**name == key) {
+            return Probe(index);
+        }
+
+        let columns = (0..channels)
+            .map(|channel| self.register(&channel_key(key, channel)))
+            .collect();
+        self.probe_names.push(Arc::from(key));
+        self.probe_columns.push(columns);
+
+        Probe(self.probe_names.len() - 1)
+    }
+
+    /// Looks up the handle of a column that has been registered or written to before.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The identifier of the column.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Column>`: The handle of the column, or `None` if no column with this key exists.
+    pub fn column(&self, key: &str) -> Option<Column> {
+        self.column_indices.get(key).copied()
+    }
+
+    /// Logs a single sample or skips if enough samples have been logged. Also applies the enforcement of every
+    /// column having the same length. Looks up the column by its key, use [`SampleLogger::write_column`] to skip that
+    /// lookup. A column that doesn't exist yet is created with the type of the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: A string slice representing the identifier of the stream of samples that this sample should be written to.
+    /// * `value`: The sample value, e.g. an `f32`, `f64`, `i64`, `bool` or [`Value`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the sample is logged successfully or if logging is disabled.
+    ///   Returns `Err` describing the error otherwise. For a logger created with [`SampleLogger::with_capacity`] or a
+    ///   streaming logger this includes writing to an unknown key, which is [`LladError::KeyNotRegistered`]. Otherwise an
+    ///   unknown key after the first frame is [`LladError::ColumnAddedLate`], and no column is created for it.
+    pub fn write(&mut self, key: &str, value: impl Into<Value>) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            if !self.is_logging_active() {
+                return Ok(()); // Don't write anything if we've seen enough samples.
+            }
+
+            let value = value.into();
+            let column = match self.column(key) {
+                Some(column) => column,
+                None if self.preallocated || self.stream.is_some() => {
+                    return Err(LladError::KeyNotRegistered {
+                        column: Arc::from(key),
+                    })
+                }
+                // Rejected before registering, as a column that can never get a value would leave every later frame
+                // incomplete.
+                None if self.stored > 0 => {
+                    return Err(LladError::ColumnAddedLate {
+                        column: Arc::from(key),
+                        sample: self.samples_seen,
+                    })
+                }
+                None => self.register_typed(key, value.column_type()),
+            };
+
+            self.write_column(column, value)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Logs a single sample to a registered column or skips if enough samples have been logged. Also applies the
+    /// enforcement of every column having exactly one value per frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `column`: The handle of the column, as returned by [`SampleLogger::register`].
+    /// * `value`: The sample value, which is converted to the type of the column, see [`Value`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the sample is logged successfully or if logging is disabled.
+    ///   Returns `Err` describing the error otherwise, which is [`LladError::TypeMismatch`] if the value can't be
+    ///   converted to the type of the column.
+    pub fn write_column(
+        &mut self,
+        column: Column,
+        value: impl Into<Value>,
+    ) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            if self.block.active {
+                let frame = self.block.frame;
+                return self.stage(column, frame, value.into());
+            }
+
+            self.write_value(column, value.into())?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a value to a column in the current frame, see [`SampleLogger::write_column`].
+    ///
+    /// # Arguments
+    ///
+    /// * `column`: The handle of the column.
+    /// * `value`: The sample value.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the sample is logged successfully or if logging has stopped,
+    ///   or `Err` describing why it is not.
+    fn write_value(&mut self, column: Column, value: Value) -> Result<(), LladError> {
+        if !self.is_logging_active() {
+            return Ok(()); // Don't write anything if we've seen enough samples.
+        }
+
+        match self.in_frame.get(column.0) {
+            Some(true) => {
+                return Err(LladError::Imbalance {
+                    column: self.names[column.0].clone(),
+                    sample: self.samples_seen,
+                })
+            }
+            Some(false) => {}
+            None => return Err(LladError::ForeignColumn(column)),
+        }
+
+        let value = match self.debug_values.columns[column.0].cast(value) {
+            Some(value) => value,
+            None => {
+                return Err(LladError::TypeMismatch {
+                    column: self.names[column.0].clone(),
+                    sample: self.samples_seen,
+                })
+            }
+        };
+
+        match &mut self.stream {
+            Some(stream) => {
+                if !stream.write(column.0, value) {
+                    return Err(self.added_late(column));
+                }
+            }
+            None => self.store(column, value)?,
+        }
+
+        self.in_frame[column.0] = true;
+        self.columns_in_frame += 1;
+
+        if let Some(watchdog) = &mut self.watchdog {
+            let caught = watchdog.check(column.0, value, self.samples_seen);
+            if caught && watchdog.action == WatchdogAction::Trigger {
+                self.trigger();
+            }
+        }
+
+        if let Some(capture) = &mut self.capture {
+            capture.observe(column, value);
+        }
+
+        if let Some(range) = self.ranges[column.0] {
+            let value = value.to_f64();
+            if !range.contains(value) {
+                return self.violation(column, value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records a value that is outside of the range of its column, see [`SampleLogger::set_range`].
+    ///
+    /// # Arguments
+    ///
+    /// * `column`: The handle of the column.
+    /// * `value`: The value, converted as in [`Value::to_f64`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns [`LladError::OutOfRange`] if range errors are enabled, `Ok(())` otherwise.
+    fn violation(&mut self, column: Column, value: f64) -> Result<(), LladError> {
+        // A preallocated logger drops the violation rather than allocating, it is still returned as an error.
+        if !self.preallocated || self.violations.len() < self.violations.capacity() {
+            self.violations.push(Recorded {
+                column: column.0,
+                sample: self.samples_seen,
+                value,
+            });
+        }
+
+        match self.range_errors {
+            true => Err(LladError::OutOfRange {
+                column: self.names[column.0].clone(),
+                sample: self.samples_seen,
+                value,
+            }),
+            false => Ok(()),
+        }
+    }
+
+    /// Logs the value of a single channel of a probe, see [`SampleLogger::write_column`].
+    ///
+    /// # Arguments
+    ///
+    /// * `probe`: The handle of the probe, as returned by [`SampleLogger::register_probe`].
+    /// * `channel`: The index of the channel.
+    /// * `value`: The sample value of the channel.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the sample is logged successfully or if logging is disabled.
+    ///   Returns [`LladError::NoSuchChannel`] if the probe has fewer channels, or the errors of
+    ///   [`SampleLogger::write_column`].
+    pub fn write_probe(
+        &mut self,
+        probe: Probe,
+        channel: usize,
+        value: impl Into<Value>,
+    ) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            let column = self.probe_column(probe, channel)?;
+            self.write_column(column, value)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Looks up the column of a channel of a probe.
+    ///
+    /// # Arguments
+    ///
+    /// * `probe`: The handle of the probe.
+    /// * `channel`: The index of the channel.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Column, LladError>`: The column of the channel, or the error if the probe has no such channel.
+    fn probe_column(&self, probe: Probe, channel: usize) -> Result<Column, LladError> {
+        let column = match self.probe_columns.get(probe.0) {
+            Some(columns) => columns.get(channel).copied(),
+            None => return Err(LladError::ForeignProbe(probe)),
+        };
+
+        column.ok_or_else(|| LladError::NoSuchChannel {
+            probe: self.probe_names[probe.0].clone(),
+            channel,
+        })
+    }
+
+    /// Logs the values of all channels of a probe at once, e.g. a frame of a nih-plug `Buffer` with
+    /// `logger.write_channels(probe, channel_samples.iter_mut().map(|sample| *sample))`.
+    ///
+    /// # Arguments
+    ///
+    /// * `probe`: The handle of the probe, as returned by [`SampleLogger::register_probe`].
+    /// * `values`: The sample value of every channel, starting at channel 0.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if all samples are logged successfully or if logging is disabled.
+    ///   Returns the first error of [`SampleLogger::write_probe`] otherwise.
+    pub fn write_channels<V: Into<Value>>(
+        &mut self,
+        probe: Probe,
+        values: impl IntoIterator<Item = V>,
+    ) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            for (channel, value) in values.into_iter().enumerate() {
+                self.write_probe(probe, channel, value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Registers the integer columns `buffer.index`, `buffer.offset` and `buffer.size`, which are filled in for every
+    /// frame with the index of the buffer it was in, its offset in that buffer and the number of samples in that buffer.
+    /// Rows with an offset of 0 are where a call to `Plugin::process` began, which is what bugs that depend on the buffer
+    /// size are usually about. Mark the start of every buffer with [`SampleLogger::begin_buffer`] or
+    /// [`SampleLogger::begin_block`].
+    ///
+    /// Like [`SampleLogger::register`], this allocates, so call it before processing starts.
+    pub fn log_buffers(&mut self) {
+        self.buffer_columns = Some(
+            [buffers::INDEX_KEY, buffers::OFFSET_KEY, buffers::SIZE_KEY]
+                .map(|key| self.register_typed(key, ColumnType::I64)),
+        );
+    }
+
+    /// Marks the start of a buffer, after which the next frame has offset 0 in a buffer with the next index, see
+    /// [`SampleLogger::log_buffers`]. Call this at the start of `Plugin::process`, [`SampleLogger::begin_block`] already
+    /// does.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`: The number of samples in the buffer.
+    pub fn begin_buffer(&mut self, len: usize) {
+        if !cfg!(feature = "disabled") {
+            self.buffer.begin(len);
+        }
+    }
+
+    /// Starts a block of frames that can be written to in any order, e.g. the input of every frame before
+    /// `Plugin::process` and the output of every frame after it. Inside a block, [`SampleLogger::write_column`] writes
+    /// to the current frame of the block and [`SampleLogger::end_frame`] moves on to the next one, while
+    /// [`SampleLogger::write_at`] writes to any frame of the block. The frames are checked and logged in order by
+    /// [`SampleLogger::end_block`]. The block also marks the start of a buffer, see [`SampleLogger::begin_buffer`].
+    ///
+    /// This only allocates if the block is larger than what was reserved with [`SampleLogger::reserve_block`].
+    ///
+    /// # Arguments
+    ///
+    /// * `len`: The number of frames in the block, usually the number of samples in the buffer.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the block is started or if logging is disabled. Returns
+    ///   [`LladError::BlockNotAllowed`] if a block was already started or if a frame is open.
+    pub fn begin_block(&mut self, len: usize) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            if self.block.active {
+                return Err(LladError::BlockNotAllowed("a block was already started."));
+            }
+
+            if self.columns_in_frame > 0 {
+                return Err(LladError::BlockNotAllowed("a frame is open."));
+            }
+
+            self.block.begin(len, self.names.len());
+            self.buffer.begin(len);
+        }
+
+        Ok(())
+    }
+
+    /// Reserves space for blocks of up to `len` frames of all registered columns, so that
+    /// [`SampleLogger::begin_block`] never allocates. Call this after registering all columns, e.g. in
+    /// `Plugin::initialize` with the maximum buffer size.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`: The maximum number of frames in a block.
+    pub fn reserve_block(&mut self, len: usize) {
+        self.block.reserve(len, self.names.len());
+    }
+
+    /// Logs a single sample to a frame of the current block, see [`SampleLogger::begin_block`].
+    ///
+    /// # Arguments
+    ///
+    /// * `column`: The handle of the column, as returned by [`SampleLogger::register`].
+    /// * `offset`: The index of the frame in the block.
+    /// * `value`: The sample value, which is converted to the type of the column.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the sample is logged successfully or if logging is disabled.
+    ///   Returns [`LladError::BlockNotAllowed`] outside of a block, [`LladError::OutsideBlock`] if the block has fewer
+    ///   frames, or the errors of [`SampleLogger::write_column`].
+    pub fn write_at(
+        &mut self,
+        column: Column,
+        offset: usize,
+        value: impl Into<Value>,
+    ) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            if !self.block.active {
+                return Err(LladError::BlockNotAllowed("no block was started."));
+            }
+
+            self.stage(column, offset, value.into())?;
+        }
+
+        Ok(())
+    }
+
+    /// Logs the value of a single channel of a probe to a frame of the current block, see
+    /// [`SampleLogger::write_at`].
+    ///
+    /// # Arguments
+    ///
+    /// * `probe`: The handle of the probe, as returned by [`SampleLogger::register_probe`].
+    /// * `channel`: The index of the channel.
+    /// * `offset`: The index of the frame in the block.
+    /// * `value`: The sample value of the channel.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the sample is logged successfully or if logging is disabled.
+    ///   Returns the errors of [`SampleLogger::write_probe`] and [`SampleLogger::write_at`] otherwise.
+    pub fn write_probe_at(
+        &mut self,
+        probe: Probe,
+        channel: usize,
+        offset: usize,
+        value: impl Into<Value>,
+    ) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            let column = self.probe_column(probe, channel)?;
+            self.write_at(column, offset, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Ends the current block and logs all of its frames in order, as if every frame had been written with
+    /// [`SampleLogger::write_column`] and ended with [`SampleLogger::end_frame`]. Every frame of the block is logged,
+    /// no matter how often [`SampleLogger::end_frame`] was called inside the block.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if all frames are logged or if logging is disabled. Returns
+    ///   [`LladError::BlockNotAllowed`] if no block was started, or the first error of logging the frames, e.g.
+    ///   [`LladError::IncompleteFrame`]. The block is ended either way, and the frame that can't be logged is dropped
+    ///   along with the rest of the block, so that the next block can be started.
+    pub fn end_block(&mut self) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            if !self.block.active {
+                return Err(LladError::BlockNotAllowed("no block was started."));
+            }
+
+            // Taken out of the logger while its frames are logged, which moves the buffers instead of allocating.
+            let mut block = std::mem::take(&mut self.block);
+            block.active = false;
+            let result = self.commit_block(&block);
+            self.block = block;
+
+            if result.is_err() {
+                self.block.clear();
+            }
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Ends the current block without logging any of its frames, e.g. after an error while writing to it, so that the
+    /// next block can be started.
+    pub(crate) fn discard_block(&mut self) {
+        self.block.active = false;
+        self.block.clear();
+    }
+
+    /// Logs all frames of a block in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `block`: The block that has been ended.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if all frames are logged, or the first error otherwise. A frame that
+    ///   can't be logged is dropped along with the rest of the block, so that the next frame starts out empty.
+    fn commit_block(&mut self, block: &Block) -> Result<(), LladError> {
+        let mut result = Ok(());
+
+        for frame in 0..block.len() {
+            for index in 0..block.width() {
+                if let Some(value) = block.get(frame, index) {
+                    match self.write_value(Column(index), value) {
+                        Ok(()) => {}
+                        // The value is logged anyway, see `SampleLogger::set_range_errors`.
+                        Err(error @ LladError::OutOfRange { .. }) => {
+                            result = result.and(Err(error))
+                        }
+                        Err(error) => {
+                            self.clear_frame();
+                            return Err(error);
+                        }
+                    }
+                }
+            }
+
+            if let Err(error) = self.commit_frame() {
+                self.clear_frame();
+                return Err(error);
+            }
+        }
+
+        result
+    }
+
+    /// Drops the values of the current frame, so that every column can get a value again. Values that were already
+    /// stored are overwritten by the next frame, as it is stored at the same index.
+    fn clear_frame(&mut self) {
+        self.in_frame.fill(false);
+        self.columns_in_frame = 0;
+    }
+
+    /// Stores a value in a frame of the current block.
+    ///
+    /// # Arguments
+    ///
+    /// * `column`: The handle of the column.
+    /// * `frame`: The index of the frame in the block.
+    /// * `value`: The sample value.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the value is stored or if logging has stopped, or `Err`
+    ///   describing why it is not.
+    fn stage(&mut self, column: Column, frame: usize, value: Value) -> Result<(), LladError> {
+        if !self.is_logging_active() {
+            return Ok(());
+        }
+
+        let value = match self.debug_values.columns.get(column.0) {
+            Some(values) => values.cast(value),
+            None => return Err(LladError::ForeignColumn(column)),
+        };
+        let sample = self.samples_seen + frame as u64;
+
+        match value.map(|value| self.block.write(frame, column.0, value)) {
+            Some(Some(true)) => Ok(()),
+            Some(Some(false)) => Err(LladError::Imbalance {
+                column: self.names[column.0].clone(),
+                sample,
+            }),
+            Some(None) if column.0 >= self.block.width() => Err(self.added_late(column)),
+            Some(None) => Err(LladError::OutsideBlock {
+                column: self.names[column.0].clone(),
+                offset: frame,
+            }),
+            None => Err(LladError::TypeMismatch {
+                column: self.names[column.0].clone(),
+                sample,
+            }),
+        }
+    }
+
+    /// Stores a value of the current frame in a column in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `column`: The handle of the column.
+    /// * `value`: The sample value, already converted to the type of the column.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the value is stored, or `Err` describing why it is not.
+    fn store(&mut self, column: Column, value: Value) -> Result<(), LladError> {
+        let values = &mut self.debug_values.columns[column.0];
+
+        // A column that is shorter than the number of stored frames was added after the first frame.
+        if values.len() < self.stored {
+            return Err(self.added_late(column));
+        }
+
+        // The pre-trigger ring buffer overwrites its oldest frame once it is full.
+        if self.cursor < values.len() {
+            values.set(self.cursor, value);
+            return Ok(());
+        }
+
+        if self.preallocated && values.len() == values.capacity() {
+            return Err(LladError::CapacityExhausted {
+                column: self.names[column.0].clone(),
+                sample: self.samples_seen,
+            });
+        }
+
+        values.push(value);
+        Ok(())
+    }
+
+    /// Creates the error for a column that was registered after the first frame had been ended.
+    fn added_late(&self, column: Column) -> LladError {
+        LladError::ColumnAddedLate {
+            column: self.names[column.0].clone(),
+            sample: self.samples_seen,
+        }
+    }
+
+    /// Ends the current frame, after which every column can get a value for the next frame. Call this once for every
+    /// sample the plugin handles, after all columns have been written. Ending a frame counts towards
+    /// `quit_after_n_samples`, and is when a [`Trigger`] is evaluated. Inside a block, see
+    /// [`SampleLogger::begin_block`], this only moves on to the next frame of the block.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the frame is complete, if logging has stopped or if logging is
+    ///   disabled. Returns [`LladError::IncompleteFrame`] naming a column that has no value in this frame, in which case
+    ///   the frame stays open. When streaming, returns [`LladError::FrameDropped`] if the writer thread fell behind.
+    pub fn end_frame(&mut self) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            if self.block.active {
+                self.block.frame += 1;
+                return Ok(());
+            }
+
+            self.commit_frame()?;
+        }
+
+        Ok(())
+    }
+
+    /// Ends the current frame, see [`SampleLogger::end_frame`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the frame is complete or if logging has stopped, or `Err`
+    ///   describing why it is not.
+    fn commit_frame(&mut self) -> Result<(), LladError> {
+        if !self.is_logging_active() {
+            return Ok(());
+        }
+
+        if let Some(columns) = self.buffer_columns {
+            let position = [self.buffer.index, self.buffer.offset, self.buffer.size];
+
+            for (column, value) in columns.into_iter().zip(position) {
+                // Already written if the frame was incomplete the last time it was ended.
+                if !self.in_frame[column.0] {
+                    self.write_value(column, Value::I64(value as i64))?;
+                }
+            }
+        }
+
+        if self.columns_in_frame != self.in_frame.len() {
+            let missing = self.in_frame.iter().position(|&in_frame| !in_frame);
+            return Err(LladError::IncompleteFrame {
+                column: self.names[missing.unwrap_or(0)].clone(),
+                sample: self.samples_seen,
+            });
+        }
+
+        self.in_frame.fill(false);
+        self.columns_in_frame = 0;
+
+        let sample = self.samples_seen;
+        self.samples_seen += 1;
+        self.buffer.advance();
+
+        let end = match &mut self.capture {
+            Some(capture) => capture.end_frame(sample),
+            None => FrameEnd::Capture,
+        };
+
+        match (end, &mut self.stream) {
+            (FrameEnd::History, Some(stream)) => stream.keep_frame(),
+            (FrameEnd::History, None) => {
+                let frames = self.history_frames();
+                self.stored = (self.stored + 1).min(frames);
+                self.cursor = (self.cursor + 1) % frames;
+            }
+            (FrameEnd::Trigger, Some(stream)) => {
+                if !stream.flush_history(sample) || !stream.end_frame() {
+                    return Err(LladError::FrameDropped { sample });
+                }
+            }
+            (FrameEnd::Trigger, None) => self.unroll_history(),
+            (FrameEnd::Capture, Some(stream)) => {
+                if !stream.end_frame() {
+                    return Err(LladError::FrameDropped { sample });
+                }
+            }
+            (FrameEnd::Capture, None) => {
+                self.stored += 1;
+                self.cursor += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of frames in the pre-trigger ring buffer in memory: the history plus the current frame.
+    fn history_frames(&self) -> usize {
+        self.capture
+            .as_ref()
+            .map_or(1, |capture| capture.pre_trigger + 1)
+    }
+
+    /// Reorders the pre-trigger ring buffer in memory so that its oldest frame comes first, after the trigger fired in
+    /// the frame that was just ended. Frames after it are appended to the columns. Reorders in place, so this never
+    /// allocates.
+    fn unroll_history(&mut self) {
+        let frames = self.history_frames();
+        self.stored = (self.stored + 1).min(frames);
+
+        if self.stored == frames {
+            let oldest = (self.cursor + 1) % frames;
+            for values in &mut self.debug_values.columns {
+                if values.len() == frames {
+                    values.rotate_left(oldest);
+                }
+            }
+        }
+
+        self.cursor = self.stored;
+    }
+
+    /// Arms a trigger, after which frames are only kept until the trigger fires, like the single-shot mode of an
+    /// oscilloscope. Up to `pre_trigger` frames before the frame in which the trigger fires are kept in a ring buffer,
+    /// and `post_trigger` frames starting at that frame are captured, after which logging stops. The trigger replaces
+    /// `quit_after_n_samples`. A trigger that never fires results in a CSV with only a header.
+    ///
+    /// This reserves the space for all captured frames, so call it before processing starts, after registering the
+    /// columns. When streaming, the ring buffer to the writer thread must be able to hold `pre_trigger + 1` rows, as the
+    /// history is handed over all at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `trigger`: The condition that fires the trigger. [`SampleLogger::trigger`] fires any trigger manually.
+    /// * `pre_trigger`: The number of frames to keep from before the frame in which the trigger fires.
+    /// * `post_trigger`: The number of frames to capture, starting at the frame in which the trigger fires.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the trigger is armed or if logging is disabled. Returns
+    ///   [`LladError::TriggerNotAllowed`] if values have already been written.
+    pub fn set_trigger(
+        &mut self,
+        trigger: Trigger,
+        pre_trigger: usize,
+        post_trigger: usize,
+    ) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            if self.samples_seen > 0 || self.columns_in_frame > 0 {
+                return Err(LladError::TriggerNotAllowed);
+            }
+
+            let capture = Capture::new(trigger, pre_trigger, post_trigger);
+            for values in &mut self.debug_values.columns {
+                values.reserve_exact(capture.frames());
+            }
+            if let Some(stream) = &mut self.stream {
+                stream.set_history(pre_trigger);
+            }
+            self.capture = Some(capture);
+        }
+
+        Ok(())
+    }
+
+    /// Fires the armed trigger manually, regardless of its condition. The current frame becomes the frame in which the
+    /// trigger fired. Does nothing if no trigger is armed or if it already fired.
+    pub fn trigger(&mut self) {
+        if let Some(capture) = &mut self.capture {
+            capture.fire();
+        }
+    }
+
+    /// Declares the range of values of a column, e.g. `-60.0..=0.0` for a gain reduction in dB or `0.0..` for an
+    /// envelope. Every value written to the column outside of this range is recorded with its frame, see
+    /// [`SampleLogger::violations`], and written to a file next to the CSV named after it with `.violations.csv`
+    /// appended. Values of any type are compared as in [`Value::to_f64`], and NaN is outside of every range.
+    ///
+    /// # Arguments
+    ///
+    /// * `column`: The handle of the column, as returned by [`SampleLogger::register`].
+    /// * `range`: The range the values of the column must be in.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the range is set, or [`LladError::ForeignColumn`] if the column
+    ///   does not belong to this logger.
+    pub fn set_range(
+        &mut self,
+        column: Column,
+        range: impl RangeBounds<f64>,
+    ) -> Result<(), LladError> {
+        match self.ranges.get_mut(column.0) {
+            Some(slot) => {
+                *slot = Some(Range::new(range));
+                Ok(())
+            }
+            None => Err(LladError::ForeignColumn(column)),
+        }
+    }
+
+    /// Sets whether writing a value outside of the range of its column returns [`LladError::OutOfRange`], so that a
+    /// violated invariant stops the plugin right where it happens. Off by default, in which case violations are only
+    /// recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `errors`: Whether violations are returned as errors.
+    pub fn set_range_errors(&mut self, errors: bool) {
+        self.range_errors = errors;
+    }
+
+    /// Reserves space for `capacity` range violations in total. A logger created with [`SampleLogger::with_capacity`]
+    /// doesn't record violations beyond that, so that writing never allocates. This allocates, so call it before
+    /// processing starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity`: The number of violations to reserve space for.
+    pub fn reserve_violations(&mut self, capacity: usize) {
+        self.violations
+            .reserve_exact(capacity.saturating_sub(self.violations.len()));
+    }
+
+    /// Returns every recorded value that was outside of the range of its column, see [`SampleLogger::set_range`]. This
+    /// allocates, so don't call it on the audio thread.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Violation>`: The violations, in the order in which the values were written.
+    pub fn violations(&self) -> Vec<Violation> {
+        self.violations
+            .iter()
+            .map(|violation| Violation {
+                column: String::from(&*self.names[violation.column]),
+                sample: violation.sample,
+                value: violation.value,
+            })
+            .collect()
+    }
+
+    /// Installs a panic hook that, when any thread panics, has the writer thread of every streaming logger write the
+    /// rows it has received so far and marks their CSV as truncated in the sidecar file, see
+    /// [`crate::Metadata::truncated`]. This matters when a panic on the audio thread takes the host down before the
+    /// logger is dropped. The hook waits for the writer threads for at most two seconds, and then runs the hook that
+    /// was installed before. Installing it more than once does nothing.
+    ///
+    /// A logger that keeps its data in memory can't be reached from the hook. It writes its data when it is dropped
+    /// instead, which also happens while the panic unwinds through the plugin, and marks the CSV as truncated then.
+    pub fn install_panic_hook() {
+        if !cfg!(feature = "disabled") {
+            panic::install();
+        }
+    }
+
+    /// Enables the watchdog, which checks every value written to a float column for NaN, infinity and subnormal
+    /// (denormal) numbers and records the first frame in which every column got one. The anomalies are listed by
+    /// [`SampleLogger::anomalies`] and written to the sidecar file, from which they are read back into
+    /// [`crate::Metadata::anomalies`]. Checking a value never allocates.
+    ///
+    /// # Arguments
+    ///
+    /// * `action`: What to do when an anomaly is caught, besides recording it. [`WatchdogAction::Trigger`] only does
+    ///   something if a trigger is armed with [`SampleLogger::set_trigger`], e.g. a [`Trigger::Manual`].
+    pub fn set_watchdog(&mut self, action: WatchdogAction) {
+        self.watchdog = Some(Watchdog::new(action, self.names.len()));
+    }
+
+    /// Returns the first anomaly of every column in which the watchdog caught one, see [`SampleLogger::set_watchdog`].
+    /// This allocates, so don't call it on the audio thread.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Anomaly>`: The anomalies, ordered by the frame in which they appeared, so the first one is where things
+    ///   first went wrong. Empty if the watchdog is not enabled or caught nothing.
+    pub fn anomalies(&self) -> Vec<Anomaly> {
+        let Some(watchdog) = &self.watchdog else {
+            return Vec::new();
+        };
+
+        let mut anomalies: Vec<Anomaly> = watchdog
+            .first
+            .iter()
+            .enumerate()
+            .filter_map(|(column, first)| {
+                first.map(|(kind, sample)| Anomaly {
+                    column: String::from(&*self.names[column]),
+                    sample,
+                    kind,
+                })
+            })
+            .collect();
+        anomalies.sort_by_key(|anomaly| anomaly.sample);
+
+        anomalies
+    }
+
+    /// Returns the index of the frame in which the trigger fired, counted from the first frame. In the CSV, this frame
+    /// is preceded by at most `pre_trigger` rows of history.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>`: The index of the trigger frame, or `None` if no trigger is armed or it has not fired yet.
+    pub fn trigger_sample(&self) -> Option<u64> {
+        self.capture
+            .as_ref()
+            .and_then(|capture| capture.triggered_at)
+    }
+
+    /// Starts writing rows to the output file on a background thread instead of keeping them in memory. Every complete
+    /// row is handed to the writer thread through a lock-free ring buffer, so [`SampleLogger::write_column`] still
+    /// never allocates, locks or does I/O, while the memory use of the logger stays fixed no matter how long it runs.
+    ///
+    /// Call this after all columns have been registered and after [`SampleLogger::set_column_order`], e.g. at the end
+    /// of `Plugin::initialize`: the header is written right away and columns can't be added afterwards. A frame that is
+    /// not ended when [`SampleLogger::write_debug_values`] is called is not written to the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity`: The number of rows the ring buffer can hold. If the writer thread falls behind by more than this,
+    ///   rows are dropped and [`SampleLogger::end_frame`] returns an error.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the writer thread is running or if logging is disabled.
+    ///   Returns `Err` if streaming was already started, if values have already been written or if creating the output
+    ///   file or the thread fails.
+    pub fn start_streaming(&mut self, capacity: usize) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            if self.stream.is_some() {
+                return Err(LladError::StreamingNotAllowed("already streaming."));
+            }
+
+            if self.samples_seen > 0 || self.columns_in_frame > 0 {
+                return Err(LladError::StreamingNotAllowed(
+                    "values have already been written.",
+                ));
+            }
+
+            let metadata = self.session_metadata();
+            if self.output_format == OutputFormat::Csv {
+                self.write_meta()?;
+            }
+
+            let order = self.ordered_columns();
+            let columns: Vec<(&str, ColumnType)> = order
+                .iter()
+                .map(|&i| (&*self.names[i], self.debug_values.columns[i].column_type()))
+                .collect();
+            let mut stream = Stream::start(
+                self.output_file.as_str(),
+                self.output_format,
+                &self.csv_dialect,
+                &columns,
+                order,
+                &metadata,
+                capacity,
+            )?;
+            if let Some(capture) = &self.capture {
+                stream.set_history(capture.pre_trigger);
+            }
+            self.stream = Some(stream);
+        }
+
+        Ok(())
+    }
+
+    /// Logs an event in the current frame, which is written to the event log next to the CSV instead of to a column.
+    /// Inside a block, see [`SampleLogger::begin_block`], the event happens in the current frame of the block.
+    ///
+    /// Logging an event with a `&'static str` label only allocates if the event log is full, reserve space for the
+    /// events with [`SampleLogger::reserve_events`] to prevent that.
+    ///
+    /// # Arguments
+    ///
+    /// * `label`: What happened, e.g. `"note_on"`.
+    /// * `payload`: The values attached to the event.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the event is logged, if logging has stopped or if logging is
+    ///   disabled. Returns [`LladError::EventLogFull`] if the logger was created with
+    ///   [`SampleLogger::with_capacity`] and the reserved space for events is used up.
+    pub fn event(
+        &mut self,
+        label: impl Into<Cow<'static, str>>,
+        payload: Payload,
+    ) -> Result<(), LladError> {
+        let offset = match self.block.active {
+            true => self.block.frame,
+            false => 0,
+        };
+
+        self.event_at(offset, label, payload)
+    }
+
+    /// Logs an event at an offset from the first frame of the current block, or from the current frame outside of a
+    /// block. This matches the timing of nih-plug's `NoteEvent`s, which is relative to the start of the buffer, as long
+    /// as the frames of the buffer are in a block or have not been ended yet. See [`SampleLogger::event`].
+    ///
+    /// # Arguments
+    ///
+    /// * `offset`: The number of frames after the first frame of the block or the current frame.
+    /// * `label`: What happened, e.g. `"note_on"`.
+    /// * `payload`: The values attached to the event.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: The same as [`SampleLogger::event`].
+    pub fn event_at(
+        &mut self,
+        offset: usize,
+        label: impl Into<Cow<'static, str>>,
+        payload: Payload,
+    ) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            if !self.is_logging_active() {
+                return Ok(());
+            }
+
+            let sample = self.samples_seen + offset as u64;
+            if self.preallocated && self.events.len() == self.events.capacity() {
+                return Err(LladError::EventLogFull { sample });
+            }
+
+            self.events.push(Event {
+                sample,
+                label: label.into(),
+                payload,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Reserves space for `capacity` events in total, so that [`SampleLogger::event`] doesn't allocate. This allocates,
+    /// so call it before processing starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity`: The number of events to reserve space for.
+    pub fn reserve_events(&mut self, capacity: usize) {
+        self.events
+            .reserve_exact(capacity.saturating_sub(self.events.len()));
+    }
+
+    /// Sets the sample rate that is written to the sidecar file, see [`crate::Metadata`]. Call this before streaming
+    /// starts, [`SampleLogger::set_buffer_config`] does this for a nih-plug plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate`: The sample rate of the plugin in Hz.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.debug_values.metadata.sample_rate = Some(sample_rate);
+    }
+
+    /// Sets the buffer size that is written to the sidecar file, see [`SampleLogger::set_sample_rate`].
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer_size`: The maximum number of samples in a buffer passed to `Plugin::process`.
+    pub fn set_buffer_size(&mut self, buffer_size: usize) {
+        self.debug_values.metadata.buffer_size = Some(buffer_size);
+    }
+
+    /// Sets the name and version of the plugin that are written to the sidecar file, see
+    /// [`SampleLogger::set_sample_rate`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the plugin.
+    /// * `version`: The version of the plugin.
+    pub fn set_plugin(&mut self, name: &str, version: &str) {
+        self.debug_values.metadata.plugin_name = Some(String::from(name));
+        self.debug_values.metadata.plugin_version = Some(String::from(version));
+    }
+
+    /// Sets the format of the output file, see [`OutputFormat`]. The default is a CSV with a sidecar file. Call this
+    /// before [`SampleLogger::start_streaming`], which creates the output file.
+    ///
+    /// # Arguments
+    ///
+    /// * `format`: The format of the output file.
+    pub fn set_output_format(&mut self, format: OutputFormat) {
+        self.output_format = format;
+    }
+
+    /// Sets the dialect of the CSV, such as its delimiter, how floats are written and whether it has an index column,
+    /// see [`CsvDialect`]. The default writes every float exactly in its shortest decimal notation. Call this before
+    /// [`SampleLogger::start_streaming`], which creates the output file. Read a CSV in another dialect than the default
+    /// with [`crate::read_csv_with_dialect`].
+    ///
+    /// # Arguments
+    ///
+    /// * `dialect`: The dialect of the CSV.
+    pub fn set_csv_dialect(&mut self, dialect: CsvDialect) {
+        self.csv_dialect = dialect;
+    }
+
+    /// Sets an explicit order for the columns in the CSV. The given keys are written first, in the given order, followed
+    /// by all other columns in the order in which they were registered or first written to. Keys of columns that don't
+    /// exist when the CSV is written are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys`: The keys of the columns that should come first in the CSV.
+    pub fn set_column_order(&mut self, keys: &[&str]) {
+        self.column_order = keys.iter().map(|&key| String::from(key)).collect();
+    }
+
+    /// Returns the logged data so far, with its columns in the order in which they were registered or first written to.
+    ///
+    /// # Returns
+    ///
+    /// * `&AudioData`: The columns that have been logged.
+    pub fn debug_values(&self) -> &AudioData {
+        &self.debug_values
+    }
+
+    /// Sets the `quit_after_n_samples` field. Useful if the plugin is applied to a large audio file and you'd like the
+    /// CSV to remain tiny.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples`: A float representing the number of samples after which logging should stop.
+    pub fn set_quit_after_n_samples(&mut self, samples: u64) {
+        self.quit_after_n_samples = Some(samples);
+    }
+
+    /// Determines whether the logging is still active based on the number of samples seen and
+    /// the optional `quit_after_n_samples` field. Returns true if quit_after_n_samples is None.
+    /// If a trigger is armed, logging is active until all frames after the trigger have been captured instead.
+    ///
+    /// # Returns
+    ///
+    /// * `bool`: Returns `true` if logging is still active (i.e., the number of samples seen is
+    ///   less than the optional `quit_after_n_samples` field or if `quit_after_n_samples` is `None`).
+    ///   Returns `false` otherwise.
+    pub fn is_logging_active(&self) -> bool {
+        let stop_after = self
+            .watchdog
+            .as_ref()
+            .and_then(|watchdog| watchdog.stop_after);
+        if stop_after.is_some_and(|sample| self.samples_seen > sample) {
+            return false;
+        }
+
+        if let Some(capture) = &self.capture {
+            return !capture.is_done();
+        }
+
+        match self.quit_after_n_samples {
+            Some(limit) => self.samples_seen < limit,
+            None => true,
+        }
+    }
+
+    /// Checks whether the logged data is correctly formatted, this function enforces that every column has a value for
+    /// every stored frame, and at most one more for the frame that is still open.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the logged data is correctly formatted.
+    ///   Returns `Err` when a column is not the same length as the number of frames.
+    fn is_logged_correctly(&self) -> Result<(), LladError> {
+        let n = self.stored;
+
+        // Checks whether all lists have n or n+1 elements.
+        if let Some(i) = self
+            .debug_values
+            .columns
+            .iter()
+            .position(|column| column.len() != n && column.len() != n + 1)
+        {
+            return Err(LladError::Imbalance {
+                column: self.names[i].clone(),
+                sample: self.samples_seen,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Determines the order in which the columns are written to the CSV, see [`SampleLogger::set_column_order`].
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<usize>`: The indices of all columns, in the order in which they should be written.
+    fn ordered_columns(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = self
+            .column_order
+            .iter()
+            .filter_map(|key| self.column(key))
+            .map(|column| column.0)
+            .collect();
+
+        for i in 0..self.debug_values.len() {
+            if !order.contains(&i) {
+                order.push(i);
+            }
+        }
+
+        order
+    }
+
+    /// Writes the sidecar file of the CSV, with the types of the columns in the order of the CSV.
+    fn write_meta(&self) -> Result<(), LladError> {
+        meta::write_meta(
+            self.output_file.as_str(),
+            &self.debug_values.metadata,
+            self.ordered_columns().into_iter().map(|i| {
+                (
+                    self.debug_values.keys[i].as_str(),
+                    self.debug_values.columns[i].column_type(),
+                )
+            }),
+        )
+    }
+
+    /// Returns the metadata of the logged data as it is written to the sidecar file, with what is known about the trigger
+    /// and the anomalies filled in.
+    fn session_metadata(&self) -> Metadata {
+        let mut metadata = self.debug_values.metadata.clone();
+        metadata.anomalies = self.anomalies();
+
+        if let Some(capture) = &self.capture {
+            metadata.trigger_sample = capture.triggered_at;
+            metadata.first_sample = capture.triggered_at.map_or(0, |sample| {
+                sample.saturating_sub(capture.pre_trigger as u64)
+            });
+        }
+
+        metadata
+    }
+
+    /// Returns a copy of the logged data as it would be written to the CSV by [`SampleLogger::write_debug_values`], with
+    /// its columns in the order of the CSV and its metadata filled in. This is what the other file formats are exported
+    /// from, e.g. by [`SampleLogger::write_vcd`]. This allocates, so don't call it on the audio thread.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<AudioData, LladError>`: The logged data, without any rows if a trigger is armed that never fired.
+    ///   Returns [`LladError::NotInMemory`] when streaming.
+    pub fn captured(&self) -> Result<AudioData, LladError> {
+        if self.stream.is_some() {
+            return Err(LladError::NotInMemory);
+        }
+
+        let fired = self
+            .capture
+            .as_ref()
+            .is_none_or(|capture| capture.triggered_at.is_some());
+        let mut data = AudioData::new();
+        data.metadata = self.session_metadata();
+
+        for i in self.ordered_columns() {
+            let values = &self.debug_values.columns[i];
+            data.push_column(
+                self.debug_values.keys[i].clone(),
+                match fired {
+                    true => values.clone(),
+                    false => ColumnData::with_capacity(values.column_type(), 0),
+                },
+            );
+        }
+
+        Ok(data)
+    }
+
+    /// Writes the logged data to the specified output file, the type of every column and the metadata to its sidecar
+    /// file, and the events and range violations, if any, to their own files. Call this on shutdown of the plugin.
+    /// When streaming, this waits for the writer thread to write all remaining rows instead.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the data is written successfully or if logging is disabled.
+    ///   Propagates any IO errors otherswise.
+    pub fn write_debug_values(&mut self) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            self.flushed = true;
+
+            if !self.events.is_empty() {
+                events::write_events(self.output_file.as_str(), &self.events)?;
+            }
+
+            if !self.violations.is_empty() {
+                range::write_violations(self.output_file.as_str(), &self.violations())?;
+            }
+
+            self.debug_values.metadata = self.session_metadata();
+
+            if let Some(mut stream) = self.stream.take() {
+                stream.finish()?;
+                // Written again now that it is known in which frame the trigger fired, which would lose the truncated
+                // record that the writer thread appended after a panic.
+                self.debug_values.metadata.truncated |= stream.truncated();
+                return match self.output_format {
+                    OutputFormat::Csv => self.write_meta(),
+                    OutputFormat::Binary => {
+                        binary::finish_file(self.output_file.as_str(), &self.debug_values.metadata)
+                    }
+                };
+            }
+
+            self.is_logged_correctly()?;
+
+            // Nothing was captured if the trigger never fired, the columns only hold the pre-trigger ring buffer.
+            let max_len = match &self.capture {
+                Some(capture) if capture.triggered_at.is_none() => 0,
+                _ => self
+                    .debug_values
+                    .columns
+                    .iter()
+                    .map(|v| v.len())
+                    .max()
+                    .unwrap_or(0),
+            };
+            let columns: Vec<(&str, &ColumnData)> = self
+                .ordered_columns()
+                .into_iter()
+                .map(|i| {
+                    (
+                        self.debug_values.keys[i].as_str(),
+                        &self.debug_values.columns[i],
+                    )
+                })
+                .collect();
+
+            match self.output_format {
+                OutputFormat::Csv => {
+                    audio_data::write_csv(
+                        self.output_file.as_str(),
+                        &columns,
+                        max_len,
+                        &self.csv_dialect,
+                        &self.debug_values.metadata,
+                    )?;
+                    self.write_meta()?;
+                }
+                // Every frame of the binary format has a value in every column, so a frame that is still open, e.g.
+                // while a panic unwinds, is left out.
+                OutputFormat::Binary => binary::write_columns(
+                    self.output_file.as_str(),
+                    &columns,
+                    max_len.min(self.stored),
+                    &self.debug_values.metadata,
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for SampleLogger {
+    /// Writes the logged data if [`SampleLogger::write_debug_values`] was not called, so that it is not lost when the
+    /// plugin is dropped without being deactivated or while a panic unwinds. Loggers that never logged anything, such
+    /// as the ones of plugin instances that a host only creates to scan them, don't write anything.
+    fn drop(&mut self) {
+        let logged = self.samples_seen > 0 || self.columns_in_frame > 0 || !self.events.is_empty();
+
+        if !cfg!(feature = "disabled") && !self.flushed && logged {
+            self.debug_values.metadata.truncated = std::thread::panicking();
+            // Errors can't be reported from here, call `write_debug_values` to receive them.
+            let _ = self.write_debug_values();
+        }
+    }
+}
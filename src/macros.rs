@@ -0,0 +1,180 @@
+use crate::{Column, LladError, SampleLogger, Value};
+
+/// The type of a logger field that is only used through the logging macros, such as [`crate::llad_log!`]. This is a
+/// [`SampleLogger`], or the zero-sized [`DisabledLogger`] with the `disabled` feature, so that the field takes no
+/// space in release builds:
+///
+/// ```ignore
+/// struct MyPlugin {
+///     logger: llad::DebugLogger,
+/// }
+///
+/// // In `Default::default`:
+/// let logger = llad::llad_logger!("my_plugin.csv");
+///
+/// // In `Plugin::process`, for every sample:
+/// llad::llad_log!(self.logger, "envelope" => self.envelope.value(), "gain" => gain)?;
+/// llad::llad_end_frame!(self.logger)?;
+/// ```
+///
+/// A plugin that implements [`crate::Logged`] or uses the methods of the logger directly should keep a
+/// [`SampleLogger`] instead.
+#[cfg(not(feature = "disabled"))]
+pub type DebugLogger = SampleLogger;
+
+/// The type of a logger field that is only used through the logging macros, see [`DebugLogger`] without the `disabled`
+/// feature. With the `disabled` feature, this is the zero-sized [`DisabledLogger`].
+#[cfg(feature = "disabled")]
+pub type DebugLogger = DisabledLogger;
+
+/// What [`DebugLogger`] is with the `disabled` feature: a logger that takes no space and can't do anything. The
+/// logging macros don't touch it at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DisabledLogger;
+
+/// A key that [`crate::llad_log!`] can write to: a `&str` is written with [`SampleLogger::write`], a [`Column`] with
+/// [`SampleLogger::write_column`].
+#[doc(hidden)]
+pub trait LogKey {
+    fn write_to(self, logger: &mut SampleLogger, value: Value) -> Result<(), LladError>;
+}
+
+impl LogKey for &str {
+    fn write_to(self, logger: &mut SampleLogger, value: Value) -> Result<(), LladError> {
+        logger.write(self, value)
+    }
+}
+
+impl LogKey for &String {
+    fn write_to(self, logger: &mut SampleLogger, value: Value) -> Result<(), LladError> {
+        logger.write(self, value)
+    }
+}
+
+impl LogKey for Column {
+    fn write_to(self, logger: &mut SampleLogger, value: Value) -> Result<(), LladError> {
+        logger.write_column(self, value)
+    }
+}
+
+/// Creates a [`DebugLogger`] that writes to the given file, see [`SampleLogger::new`]. With the `disabled` feature,
+/// this is a [`DisabledLogger`] and the file name is not evaluated.
+#[cfg(not(feature = "disabled"))]
+#[macro_export]
+macro_rules! llad_logger {
+    ($output_file:expr) => {
+        $crate::SampleLogger::new(::std::string::String::from($output_file))
+    };
+}
+
+/// Creates a [`DebugLogger`] that writes to the given file, see [`SampleLogger::new`]. With the `disabled` feature,
+/// this is a [`DisabledLogger`] and the file name is not evaluated.
+#[cfg(feature = "disabled")]
+#[macro_export]
+macro_rules! llad_logger {
+    ($output_file:expr) => {
+        $crate::DisabledLogger
+    };
+}
+
+/// Logs one or more values to the current frame, as `key => value` pairs where the key is a `&str` or a
+/// [`crate::Column`] and the value anything that converts into a [`crate::Value`]. Evaluates to a
+/// `Result<(), LladError>` with the first error of [`crate::SampleLogger::write`] or
+/// [`crate::SampleLogger::write_column`], and stops at that error.
+///
+/// With the `disabled` feature, this is `Ok(())` and neither the logger nor the keys and values are evaluated, so
+/// expensive probe computations cost nothing in release builds.
+///
+/// ```ignore
+/// llad_log!(self.logger, "input" => sample, self.envelope_column => self.envelope.value())?;
+/// ```
+#[cfg(not(feature = "disabled"))]
+#[macro_export]
+macro_rules! llad_log {
+    ($logger:expr, $($key:expr => $value:expr),+ $(,)?) => {
+        'llad_log: {
+            $(
+                // Evaluated before the logger is borrowed, so the value can borrow the rest of the plugin.
+                let value = $crate::Value::from($value);
+                let written = $crate::LogKey::write_to($key, &mut $logger, value);
+                if written.is_err() {
+                    break 'llad_log written;
+                }
+            )+
+            ::std::result::Result::<(), $crate::LladError>::Ok(())
+        }
+    };
+}
+
+/// Logs one or more values to the current frame, see the documentation without the `disabled` feature. With the
+/// `disabled` feature, this is `Ok(())` and nothing is evaluated.
+#[cfg(feature = "disabled")]
+#[macro_export]
+macro_rules! llad_log {
+    ($logger:expr, $($key:expr => $value:expr),+ $(,)?) => {
+        ::std::result::Result::<(), $crate::LladError>::Ok(())
+    };
+}
+
+/// Logs an event with a label and an optional [`crate::Payload`], see [`crate::SampleLogger::event`]. Evaluates to
+/// its `Result`. With the `disabled` feature, this is `Ok(())` and nothing is evaluated.
+#[cfg(not(feature = "disabled"))]
+#[macro_export]
+macro_rules! llad_event {
+    ($logger:expr, $label:expr) => {
+        $crate::llad_event!($logger, $label, $crate::Payload::new())
+    };
+    ($logger:expr, $label:expr, $payload:expr) => {
+        $crate::SampleLogger::event(&mut $logger, $label, $payload)
+    };
+}
+
+/// Logs an event with a label and an optional [`crate::Payload`], see the documentation without the `disabled`
+/// feature. With the `disabled` feature, this is `Ok(())` and nothing is evaluated.
+#[cfg(feature = "disabled")]
+#[macro_export]
+macro_rules! llad_event {
+    ($logger:expr, $label:expr $(, $payload:expr)?) => {
+        ::std::result::Result::<(), $crate::LladError>::Ok(())
+    };
+}
+
+/// Ends the current frame, see [`crate::SampleLogger::end_frame`]. Evaluates to its `Result`. With the `disabled`
+/// feature, this is `Ok(())` and the logger is not evaluated.
+#[cfg(not(feature = "disabled"))]
+#[macro_export]
+macro_rules! llad_end_frame {
+    ($logger:expr) => {
+        $crate::SampleLogger::end_frame(&mut $logger)
+    };
+}
+
+/// Ends the current frame, see the documentation without the `disabled` feature. With the `disabled` feature, this is
+/// `Ok(())` and the logger is not evaluated.
+#[cfg(feature = "disabled")]
+#[macro_export]
+macro_rules! llad_end_frame {
+    ($logger:expr) => {
+        ::std::result::Result::<(), $crate::LladError>::Ok(())
+    };
+}
+
+/// Writes the logged data, see [`crate::SampleLogger::write_debug_values`]. Evaluates to its `Result`. With the
+/// `disabled` feature, this is `Ok(())` and the logger is not evaluated.
+#[cfg(not(feature = "disabled"))]
+#[macro_export]
+macro_rules! llad_flush {
+    ($logger:expr) => {
+        $crate::SampleLogger::write_debug_values(&mut $logger)
+    };
+}
+
+/// Writes the logged data, see the documentation without the `disabled` feature. With the `disabled` feature, this is
+/// `Ok(())` and the logger is not evaluated.
+#[cfg(feature = "disabled")]
+#[macro_export]
+macro_rules! llad_flush {
+    ($logger:expr) => {
+        ::std::result::Result::<(), $crate::LladError>::Ok(())
+    };
+}
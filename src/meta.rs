@@ -0,0 +1,237 @@
+use std::{
+    fs::File,
+    io::{ErrorKind, Read, Write},
+    str::FromStr,
+};
+
+use crate::{Anomaly, AnomalyKind, ColumnType, LladError};
+
+/// What is known about the session in which a CSV was logged, beyond its columns. Stored in the sidecar file next to
+/// the CSV and read back by [`crate::read_csv_as_audio_data`] into [`crate::AudioData::metadata`].
+///
+/// # Fields
+///
+/// * `sample_rate`: The sample rate of the plugin in Hz, from which [`crate::AudioData::time_axis`] is computed.
+/// * `buffer_size`: The maximum number of samples in a buffer passed to `Plugin::process`.
+/// * `plugin_name`: The name of the plugin.
+/// * `plugin_version`: The version of the plugin.
+/// * `timestamp`: When the logger was created, in seconds since the Unix epoch.
+/// * `first_sample`: The index of the frame of the first row, counted from the first frame the logger saw. Only
+///   non-zero for the CSV of a [`crate::Trigger`].
+/// * `trigger_sample`: The index of the frame in which the trigger fired, see [`crate::SampleLogger::trigger_sample`].
+/// * `anomalies`: The first anomaly of every column caught by the watchdog, see [`crate::SampleLogger::anomalies`].
+/// * `truncated`: Whether the CSV was written because the plugin panicked, so it ends where the panic happened, see
+///   [`crate::SampleLogger::install_panic_hook`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    pub sample_rate: Option<f32>,
+    pub buffer_size: Option<usize>,
+    pub plugin_name: Option<String>,
+    pub plugin_version: Option<String>,
+    pub timestamp: Option<u64>,
+    pub first_sample: u64,
+    pub trigger_sample: Option<u64>,
+    pub anomalies: Vec<Anomaly>,
+    pub truncated: bool,
+}
+
+/// Returns the name of the sidecar file of a CSV, which records what the CSV itself can't, such as the type of every
+/// column. The sidecar is a CSV without a header, where the first field of every record says what the record describes:
+///
+/// * `type,<column>,<type>[,<label>...]`: The [`ColumnType`] of a column as `f32`, `f64`, `i64`, `bool` or `enum`,
+///   followed by the labels of an enum, in the order of the columns in the CSV.
+/// * `sample_rate,<hz>`, `buffer_size,<samples>`, `plugin_name,<name>`, `plugin_version,<version>`,
+///   `timestamp,<seconds>`, `first_sample,<frame>` and `trigger_sample,<frame>`: The fields of the [`Metadata`], each
+///   only written if it is known.
+/// * `truncated,true`: The CSV was written because the plugin panicked.
+/// * `anomaly,<column>,<kind>,<frame>`: The first anomaly in a column caught by the watchdog, where the kind is `nan`,
+///   `inf` or `subnormal`.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the CSV.
+///
+/// # Returns
+///
+/// * `String`: The name of the sidecar file.
+pub(crate) fn meta_file(filename: &str) -> String {
+    format!("{filename}.meta")
+}
+
+/// Writes the sidecar file of a CSV, see [`meta_file`].
+///
+/// # Arguments
+///
+/// * `filename`: The name of the CSV.
+/// * `metadata`: The metadata.
+/// * `columns`: The key and type of every column, in the order of the CSV.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the sidecar file is written, or the error while writing it.
+pub(crate) fn write_meta<'a>(
+    filename: &str,
+    metadata: &Metadata,
+    columns: impl Iterator<Item = (&'a str, ColumnType)>,
+) -> Result<(), LladError> {
+    write_records(File::create(meta_file(filename))?, metadata, columns)
+}
+
+/// Writes the records of a sidecar file, see [`meta_file`], to any writer. The type records are written in the order
+/// of the columns.
+///
+/// # Arguments
+///
+/// * `writer`: Where to write the records to.
+/// * `metadata`: The metadata.
+/// * `columns`: The key and type of every column.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the records are written, or the error while writing them.
+pub(crate) fn write_records<'a>(
+    writer: impl Write,
+    metadata: &Metadata,
+    columns: impl Iterator<Item = (&'a str, ColumnType)>,
+) -> Result<(), LladError> {
+    let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(writer);
+
+    let fields = [
+        (
+            "sample_rate",
+            metadata.sample_rate.map(|rate| rate.to_string()),
+        ),
+        (
+            "buffer_size",
+            metadata.buffer_size.map(|size| size.to_string()),
+        ),
+        ("plugin_name", metadata.plugin_name.clone()),
+        ("plugin_version", metadata.plugin_version.clone()),
+        ("timestamp", metadata.timestamp.map(|time| time.to_string())),
+        ("first_sample", Some(metadata.first_sample.to_string())),
+        (
+            "trigger_sample",
+            metadata.trigger_sample.map(|sample| sample.to_string()),
+        ),
+        (
+            "truncated",
+            metadata.truncated.then(|| String::from("true")),
+        ),
+    ];
+    for (name, value) in fields {
+        if let Some(value) = value {
+            writer.write_record([name, value.as_str()])?;
+        }
+    }
+
+    for anomaly in &metadata.anomalies {
+        let sample = anomaly.sample.to_string();
+        writer.write_record([
+            "anomaly",
+            anomaly.column.as_str(),
+            anomaly.kind.name(),
+            sample.as_str(),
+        ])?;
+    }
+
+    for (key, column_type) in columns {
+        let mut record = vec!["type", key, column_type.name()];
+        if let ColumnType::Enum(labels) = &column_type {
+            record.extend(labels.iter().map(String::as_str));
+        }
+        writer.write_record(&record)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the types of the columns and the [`Metadata`] from the sidecar file of a CSV, see [`meta_file`]. Records
+/// that are not understood are skipped, so that sidecar files written by newer versions can still be read.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the CSV.
+///
+/// # Returns
+///
+/// * `Result<(Vec<(String, ColumnType)>, Metadata), LladError>`: The key and type of every column in the sidecar
+///   file, in the order of the CSV, and the metadata, which are empty if the CSV has no sidecar file. Returns `Err` if
+///   the sidecar file exists but can't be read.
+pub(crate) fn read_meta(
+    filename: &str,
+) -> Result<(Vec<(String, ColumnType)>, Metadata), LladError> {
+    let file = match File::open(meta_file(filename)) {
+        Ok(file) => file,
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            return Ok((Vec::new(), Metadata::default()))
+        }
+        Err(error) => return Err(error.into()),
+    };
+
+    read_records(file)
+}
+
+/// Reads the records of a sidecar file, see [`meta_file`], from any reader.
+///
+/// # Arguments
+///
+/// * `reader`: Where to read the records from.
+///
+/// # Returns
+///
+/// * `Result<(Vec<(String, ColumnType)>, Metadata), LladError>`: The key and type of every column in the order of the
+///   type records, and the metadata. Returns `Err` if the records can't be read.
+pub(crate) fn read_records(
+    reader: impl Read,
+) -> Result<(Vec<(String, ColumnType)>, Metadata), LladError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(reader);
+    let mut types = Vec::new();
+    let mut metadata = Metadata::default();
+
+    for record in reader.records() {
+        let record = record?;
+        let value = record.get(1).unwrap_or_default();
+
+        match record.get(0) {
+            Some("type") => {
+                let labels = record.iter().skip(3).map(String::from).collect();
+                let column_type = record
+                    .get(2)
+                    .and_then(|name| ColumnType::from_name(name, labels));
+                if let Some(column_type) = column_type {
+                    types.push((String::from(value), column_type));
+                }
+            }
+            Some("sample_rate") => metadata.sample_rate = parse(value),
+            Some("buffer_size") => metadata.buffer_size = parse(value),
+            Some("plugin_name") => metadata.plugin_name = Some(String::from(value)),
+            Some("plugin_version") => metadata.plugin_version = Some(String::from(value)),
+            Some("timestamp") => metadata.timestamp = parse(value),
+            Some("first_sample") => metadata.first_sample = parse(value).unwrap_or(0),
+            Some("trigger_sample") => metadata.trigger_sample = parse(value),
+            Some("truncated") => metadata.truncated = parse(value).unwrap_or(false),
+            Some("anomaly") => {
+                let kind = record.get(2).and_then(AnomalyKind::from_name);
+                if let (Some(kind), Some(sample)) = (kind, record.get(3).and_then(parse)) {
+                    metadata.anomalies.push(Anomaly {
+                        column: String::from(value),
+                        sample,
+                        kind,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok((types, metadata))
+}
+
+/// Parses a field of the [`Metadata`], which is skipped like an unknown record if it can't be parsed.
+fn parse<T: FromStr>(value: &str) -> Option<T> {
+    value.parse().ok()
+}
@@ -0,0 +1,188 @@
+use nih_plug::prelude::*;
+
+use crate::{LladError, Payload, SampleLogger};
+
+impl SampleLogger {
+    /// Logs a nih-plug `NoteEvent` to the event log, at the sample given by its timing. Call this for every event
+    /// returned by `ProcessContext::next_event`, before the frames of the buffer have been ended or inside a block, see
+    /// [`SampleLogger::event_at`]. [`crate::Probed`] does this for every event the wrapped plugin receives.
+    ///
+    /// The label is the type of the event in snake case, e.g. `note_on` or `midi_cc`, and the payload has its
+    /// `timing`, `channel`, `note`, `voice` and value, e.g. `velocity`, where the event has them. Events that can't be
+    /// summarized in a payload, such as SysEx messages, are logged as `other` with only their timing.
+    ///
+    /// # Arguments
+    ///
+    /// * `event`: The event received by the plugin.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: The same as [`SampleLogger::event`].
+    pub fn note_event<S: SysExMessage>(&mut self, event: &NoteEvent<S>) -> Result<(), LladError> {
+        let timing = event.timing();
+        let payload = Payload::new().with("timing", timing as i64);
+
+        let (label, payload) = match *event {
+            NoteEvent::NoteOn {
+                voice_id,
+                channel,
+                note,
+                velocity,
+                ..
+            } => (
+                "note_on",
+                note_payload(payload, voice_id, channel, note).with("velocity", velocity),
+            ),
+            NoteEvent::NoteOff {
+                voice_id,
+                channel,
+                note,
+                velocity,
+                ..
+            } => (
+                "note_off",
+                note_payload(payload, voice_id, channel, note).with("velocity", velocity),
+            ),
+            NoteEvent::Choke {
+                voice_id,
+                channel,
+                note,
+                ..
+            } => ("choke", note_payload(payload, voice_id, channel, note)),
+            NoteEvent::VoiceTerminated {
+                voice_id,
+                channel,
+                note,
+                ..
+            } => (
+                "voice_terminated",
+                note_payload(payload, voice_id, channel, note),
+            ),
+            NoteEvent::PolyPressure {
+                voice_id,
+                channel,
+                note,
+                pressure,
+                ..
+            } => (
+                "poly_pressure",
+                note_payload(payload, voice_id, channel, note).with("pressure", pressure),
+            ),
+            NoteEvent::PolyVolume {
+                voice_id,
+                channel,
+                note,
+                gain,
+                ..
+            } => (
+                "poly_volume",
+                note_payload(payload, voice_id, channel, note).with("gain", gain),
+            ),
+            NoteEvent::PolyPan {
+                voice_id,
+                channel,
+                note,
+                pan,
+                ..
+            } => (
+                "poly_pan",
+                note_payload(payload, voice_id, channel, note).with("pan", pan),
+            ),
+            NoteEvent::PolyTuning {
+                voice_id,
+                channel,
+                note,
+                tuning,
+                ..
+            } => (
+                "poly_tuning",
+                note_payload(payload, voice_id, channel, note).with("tuning", tuning),
+            ),
+            NoteEvent::PolyVibrato {
+                voice_id,
+                channel,
+                note,
+                vibrato,
+                ..
+            } => (
+                "poly_vibrato",
+                note_payload(payload, voice_id, channel, note).with("vibrato", vibrato),
+            ),
+            NoteEvent::PolyExpression {
+                voice_id,
+                channel,
+                note,
+                expression,
+                ..
+            } => (
+                "poly_expression",
+                note_payload(payload, voice_id, channel, note).with("expression", expression),
+            ),
+            NoteEvent::PolyBrightness {
+                voice_id,
+                channel,
+                note,
+                brightness,
+                ..
+            } => (
+                "poly_brightness",
+                note_payload(payload, voice_id, channel, note).with("brightness", brightness),
+            ),
+            NoteEvent::MidiChannelPressure {
+                channel, pressure, ..
+            } => (
+                "midi_channel_pressure",
+                payload
+                    .with("channel", channel as i64)
+                    .with("pressure", pressure),
+            ),
+            NoteEvent::MidiPitchBend { channel, value, .. } => (
+                "midi_pitch_bend",
+                payload.with("channel", channel as i64).with("value", value),
+            ),
+            NoteEvent::MidiCC {
+                channel, cc, value, ..
+            } => (
+                "midi_cc",
+                payload
+                    .with("channel", channel as i64)
+                    .with("cc", cc as i64)
+                    .with("value", value),
+            ),
+            NoteEvent::MidiProgramChange {
+                channel, program, ..
+            } => (
+                "midi_program_change",
+                payload
+                    .with("channel", channel as i64)
+                    .with("program", program as i64),
+            ),
+            _ => ("other", payload),
+        };
+
+        self.event_at(timing as usize, label, payload)
+    }
+}
+
+/// Adds the fields that identify the note of an event to a payload.
+///
+/// # Arguments
+///
+/// * `payload`: The payload to add the fields to.
+/// * `voice_id`: The voice of the event, only added if it has one.
+/// * `channel`: The MIDI channel of the event.
+/// * `note`: The MIDI note number of the event.
+///
+/// # Returns
+///
+/// * `Payload`: The payload with the fields added.
+fn note_payload(payload: Payload, voice_id: Option<i32>, channel: u8, note: u8) -> Payload {
+    let payload = payload
+        .with("channel", channel as i64)
+        .with("note", note as i64);
+
+    match voice_id {
+        Some(voice_id) => payload.with("voice", voice_id),
+        None => payload,
+    }
+}
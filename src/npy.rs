@@ -0,0 +1,504 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use crate::{AudioData, ColumnData, LladError, Metadata, SampleLogger};
+
+/// The prefix of the arrays in an `.npz` written by [`write_npz`] that are not columns.
+const META_PREFIX: &str = "__llad__.";
+
+/// The CRC-32 of every byte, for the checksums of the entries of an `.npz`.
+const CRC_TABLE: [u32; 256] = crc_table();
+
+/// The date of the entries of an `.npz` in MS-DOS format, 1980-01-01, the earliest date it can hold.
+const DOS_DATE: u16 = 1 << 5 | 1;
+
+impl SampleLogger {
+    /// Writes the logged data as an `.npz` archive of NumPy arrays, see [`write_npz`]. Call this on shutdown of the
+    /// plugin, next to [`SampleLogger::write_debug_values`].
+    ///
+    /// # Arguments
+    ///
+    /// * `filename`: The name of the `.npz` file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the file is written or if logging is disabled. Returns
+    ///   [`LladError::NotInMemory`] when streaming, or the error while writing the file.
+    pub fn write_npz(&self, filename: String) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            write_npz(filename, &self.captured()?)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes a column as a NumPy `.npy` file, which `numpy.load` reads as a one-dimensional array with the exact values
+/// of the column. Float columns become `float32` or `float64` arrays, `i64` columns `int64` arrays and `bool` columns
+/// `bool` arrays. Enum columns become `uint32` arrays of the indices of their variants, without their labels.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the `.npy` file.
+/// * `values`: The column to write.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the file is written, or the error while writing it.
+pub fn write_npy(filename: String, values: &ColumnData) -> Result<(), LladError> {
+    let mut file = BufWriter::new(File::create(filename)?);
+    file.write_all(&column_array(values))?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Writes columns as a NumPy `.npz` archive, which `numpy.load` reads as a mapping from the key of every column to
+/// its array, see [`write_npy`]. The [`Metadata`] is stored next to the columns as scalar arrays whose keys start with
+/// `__llad__.`, such as `__llad__.sample_rate` and `__llad__.first_sample`, where fields that are unknown are left
+/// out. The labels of an enum column are stored as the string array `__llad__.labels.<key>`.
+///
+/// The archive is an uncompressed zip file, so loading it costs no more than loading the arrays themselves. It can't
+/// be larger than 4 GiB.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the `.npz` file.
+/// * `data`: The columns to write, e.g. as read by [`crate::read_csv_as_audio_data`].
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the file is written, [`LladError::ArchiveTooLarge`] if it would be
+///   larger than 4 GiB, or the error while writing it.
+pub fn write_npz(filename: String, data: &AudioData) -> Result<(), LladError> {
+    let mut archive = Archive::new(BufWriter::new(File::create(filename)?));
+
+    for (key, values) in data.iter() {
+        archive.add(key, &column_array(values))?;
+
+        if let ColumnData::Enum { labels, .. } = values {
+            archive.add(
+                &format!("{META_PREFIX}labels.{key}"),
+                &string_array(labels, Some(labels.len())),
+            )?;
+        }
+    }
+
+    for (name, array) in metadata_arrays(data.metadata()) {
+        archive.add(&format!("{META_PREFIX}{name}"), &array)?;
+    }
+
+    archive.finish()
+}
+
+/// Returns the fields of the metadata that are known as `.npy` files of scalar arrays.
+///
+/// # Arguments
+///
+/// * `metadata`: The metadata of the columns.
+///
+/// # Returns
+///
+/// * `Vec<(&'static str, Vec<u8>)>`: The name and contents of the array of every known field.
+fn metadata_arrays(metadata: &Metadata) -> Vec<(&'static str, Vec<u8>)> {
+    let mut arrays = Vec::new();
+    let scalar = |value: u64| array("<u8", None, &value.to_le_bytes());
+
+    if let Some(sample_rate) = metadata.sample_rate {
+        arrays.push((
+            "sample_rate",
+            array("<f4", None, &sample_rate.to_le_bytes()),
+        ));
+    }
+    if let Some(buffer_size) = metadata.buffer_size {
+        arrays.push(("buffer_size", scalar(buffer_size as u64)));
+    }
+    if let Some(name) = &metadata.plugin_name {
+        arrays.push((
+            "plugin_name",
+            string_array(std::slice::from_ref(name), None),
+        ));
+    }
+    if let Some(version) = &metadata.plugin_version {
+        arrays.push((
+            "plugin_version",
+            string_array(std::slice::from_ref(version), None),
+        ));
+    }
+    if let Some(timestamp) = metadata.timestamp {
+        arrays.push(("timestamp", scalar(timestamp)));
+    }
+    arrays.push(("first_sample", scalar(metadata.first_sample)));
+    if let Some(trigger_sample) = metadata.trigger_sample {
+        arrays.push(("trigger_sample", scalar(trigger_sample)));
+    }
+    arrays.push((
+        "truncated",
+        array("|b1", None, &[u8::from(metadata.truncated)]),
+    ));
+
+    arrays
+}
+
+/// Returns the contents of the `.npy` file of a column, see [`write_npy`].
+fn column_array(values: &ColumnData) -> Vec<u8> {
+    let len = Some(values.len());
+
+    match values {
+        ColumnData::F32(values) => {
+            array("<f4", len, &le_bytes(values, |value| value.to_le_bytes()))
+        }
+        ColumnData::F64(values) => {
+            array("<f8", len, &le_bytes(values, |value| value.to_le_bytes()))
+        }
+        ColumnData::I64(values) => {
+            array("<i8", len, &le_bytes(values, |value| value.to_le_bytes()))
+        }
+        ColumnData::Bool(values) => {
+            array("|b1", len, &le_bytes(values, |&value| [u8::from(value)]))
+        }
+        ColumnData::Enum { values, .. } => {
+            array("<u4", len, &le_bytes(values, |value| value.to_le_bytes()))
+        }
+    }
+}
+
+/// Converts values to their little-endian bytes, one after the other.
+fn le_bytes<T, const N: usize>(values: &[T], to_bytes: impl Fn(&T) -> [u8; N]) -> Vec<u8> {
+    values.iter().flat_map(to_bytes).collect()
+}
+
+/// Returns the contents of the `.npy` file of an array of strings, which NumPy stores as fixed-width UTF-32.
+///
+/// # Arguments
+///
+/// * `strings`: The strings.
+/// * `len`: The length of the array, or `None` for a scalar of the first string.
+///
+/// # Returns
+///
+/// * `Vec<u8>`: The contents of the `.npy` file.
+fn string_array(strings: &[String], len: Option<usize>) -> Vec<u8> {
+    let width = strings
+        .iter()
+        .map(|string| string.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut data = Vec::with_capacity(strings.len() * width * 4);
+    for string in strings {
+        let chars = string.chars().map(u32::from).chain(std::iter::repeat(0));
+        data.extend(chars.take(width).flat_map(u32::to_le_bytes));
+    }
+
+    array(&format!("<U{width}"), len, &data)
+}
+
+/// Returns the contents of a version 1.0 `.npy` file: the magic string, the header describing the array, padded so
+/// that the data is aligned to 64 bytes, and the data.
+///
+/// # Arguments
+///
+/// * `descr`: The NumPy type string of the elements, e.g. `<f4`.
+/// * `len`: The length of the one-dimensional array, or `None` for a scalar.
+/// * `data`: The elements, in C order.
+///
+/// # Returns
+///
+/// * `Vec<u8>`: The contents of the `.npy` file.
+fn array(descr: &str, len: Option<usize>, data: &[u8]) -> Vec<u8> {
+    let shape = match len {
+        Some(len) => format!("({len},)"),
+        None => String::from("()"),
+    };
+    let mut header = format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {shape}, }}");
+    // The magic string, version and header length take 10 bytes, and the header ends with a newline.
+    let padding = (64 - (10 + header.len() + 1) % 64) % 64;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    let mut bytes = Vec::with_capacity(10 + header.len() + data.len());
+    bytes.extend_from_slice(b"\x93NUMPY\x01\x00");
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend_from_slice(data);
+
+    bytes
+}
+
+/// An entry of a zip file, as it is listed in the central directory.
+///
+/// # Fields
+///
+/// * `name`: The name of the file.
+/// * `crc`: The CRC-32 of its contents.
+/// * `size`: The size of its contents.
+/// * `offset`: The offset of its local header from the start of the zip file.
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// A zip file with uncompressed entries, which is all an `.npz` needs.
+///
+/// # Fields
+///
+/// * `writer`: The zip file.
+/// * `entries`: The entries written so far.
+/// * `offset`: The number of bytes written so far.
+struct Archive<W: Write> {
+    writer: W,
+    entries: Vec<Entry>,
+    offset: u64,
+}
+
+impl<W: Write> Archive<W> {
+    /// Starts an empty zip file.
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            entries: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// Writes a `.npy` file to the zip file.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: The key of the array, without `.npy`.
+    /// * `contents`: The contents of the `.npy` file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the entry is written, [`LladError::ArchiveTooLarge`] if the zip
+    ///   file would be larger than 4 GiB, or the error while writing it.
+    fn add(&mut self, key: &str, contents: &[u8]) -> Result<(), LladError> {
+        let entry = Entry {
+            name: format!("{key}.npy"),
+            crc: crc32(contents),
+            size: u32::try_from(contents.len()).map_err(|_| LladError::ArchiveTooLarge)?,
+            offset: u32::try_from(self.offset).map_err(|_| LladError::ArchiveTooLarge)?,
+        };
+
+        let mut header = Vec::with_capacity(30 + entry.name.len());
+        header.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+        header.extend_from_slice(&20_u16.to_le_bytes());
+        header.extend_from_slice(&entry_fields(&entry));
+        header.extend_from_slice(&0_u16.to_le_bytes());
+        header.extend_from_slice(entry.name.as_bytes());
+
+        self.write(&header)?;
+        self.write(contents)?;
+        self.entries.push(entry);
+
+        Ok(())
+    }
+
+    /// Writes the central directory and flushes the zip file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the zip file is complete, [`LladError::ArchiveTooLarge`] if it
+    ///   would be larger than 4 GiB, or the error while writing it.
+    fn finish(mut self) -> Result<(), LladError> {
+        let start = u32::try_from(self.offset).map_err(|_| LladError::ArchiveTooLarge)?;
+        let entries = u16::try_from(self.entries.len()).map_err(|_| LladError::ArchiveTooLarge)?;
+
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            directory.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+            // Made by and needed to extract: version 2.0.
+            directory.extend_from_slice(&20_u16.to_le_bytes());
+            directory.extend_from_slice(&20_u16.to_le_bytes());
+            directory.extend_from_slice(&entry_fields(entry));
+            // No extra field, comment, disk number, internal or external attributes.
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&entry.offset.to_le_bytes());
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+        self.write(&directory)?;
+        let size = u32::try_from(directory.len()).map_err(|_| LladError::ArchiveTooLarge)?;
+
+        let mut end = Vec::with_capacity(22);
+        end.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+        end.extend_from_slice(&[0; 4]);
+        end.extend_from_slice(&entries.to_le_bytes());
+        end.extend_from_slice(&entries.to_le_bytes());
+        end.extend_from_slice(&size.to_le_bytes());
+        end.extend_from_slice(&start.to_le_bytes());
+        end.extend_from_slice(&0_u16.to_le_bytes());
+        self.write(&end)?;
+
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes bytes to the zip file and keeps track of the offset.
+    fn write(&mut self, bytes: &[u8]) -> Result<(), LladError> {
+        self.writer.write_all(bytes)?;
+        self.offset += bytes.len() as u64;
+
+        Ok(())
+    }
+}
+
+/// Returns the fields that the local header and the central directory share for an uncompressed entry, from the
+/// flags up to and including the length of the name.
+fn entry_fields(entry: &Entry) -> Vec<u8> {
+    let mut fields = Vec::with_capacity(24);
+    // No flags, stored without compression, at midnight.
+    fields.extend_from_slice(&[0; 6]);
+    fields.extend_from_slice(&DOS_DATE.to_le_bytes());
+    fields.extend_from_slice(&entry.crc.to_le_bytes());
+    fields.extend_from_slice(&entry.size.to_le_bytes());
+    fields.extend_from_slice(&entry.size.to_le_bytes());
+    fields.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+
+    fields
+}
+
+/// Computes the CRC-32 of bytes, as used by zip files.
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[usize::from((crc as u8) ^ byte)] ^ (crc >> 8)
+    })
+}
+
+/// Computes [`CRC_TABLE`] for the reflected polynomial `0xEDB88320`.
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColumnType, Value};
+
+    fn u16_at(bytes: &[u8], offset: usize) -> usize {
+        usize::from(u16::from_le_bytes([bytes[offset], bytes[offset + 1]]))
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn checksum_is_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn arrays_have_an_aligned_npy_header() {
+        let values = ColumnData::F32(vec![1.0, -0.5, f32::MIN_POSITIVE / 2.0]);
+        let bytes = column_array(&values);
+
+        assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+        let data = 10 + u16_at(&bytes, 8);
+        assert_eq!(data % 64, 0);
+        let header = std::str::from_utf8(&bytes[10..data]).unwrap();
+        assert_eq!(
+            header.trim_end(),
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (3,), }"
+        );
+        assert!(header.ends_with('\n'));
+
+        let floats: Vec<u32> = bytes[data..].chunks(4).map(|b| u32_at(b, 0)).collect();
+        let expected: Vec<u32> = values
+            .as_f32()
+            .unwrap()
+            .iter()
+            .map(|v| v.to_bits())
+            .collect();
+        assert_eq!(floats, expected);
+
+        let scalar = array("<u8", None, &7_u64.to_le_bytes());
+        let data = 10 + u16_at(&scalar, 8);
+        assert_eq!(data % 64, 0);
+        let header = std::str::from_utf8(&scalar[10..data]).unwrap();
+        assert!(header.contains("'shape': ()"));
+        assert_eq!(scalar[data..], 7_u64.to_le_bytes());
+    }
+
+    #[test]
+    fn archive_holds_every_column_and_the_metadata() {
+        let mut data = AudioData::new();
+        data.push_column(String::from("x"), ColumnData::F64(vec![0.1, 5e-324]));
+        let mut state = ColumnData::with_capacity(ColumnType::enumeration(&["idle", "run"]), 2);
+        state.push(Value::Enum(1));
+        state.push(Value::Enum(0));
+        data.push_column(String::from("state"), state);
+        data.metadata.sample_rate = Some(48000.0);
+
+        let filename = std::env::temp_dir().join("llad.npz");
+        write_npz(filename.to_string_lossy().into_owned(), &data).unwrap();
+        let bytes = std::fs::read(filename).unwrap();
+
+        // Walks the local headers, which are followed by the uncompressed contents.
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while u32_at(&bytes, offset) == 0x0403_4b50 {
+            let crc = u32_at(&bytes, offset + 14);
+            let size = u32_at(&bytes, offset + 18) as usize;
+            assert_eq!(u32_at(&bytes, offset + 22) as usize, size);
+            let name_len = u16_at(&bytes, offset + 26);
+            let name = std::str::from_utf8(&bytes[offset + 30..offset + 30 + name_len]).unwrap();
+            let contents = &bytes[offset + 30 + name_len..offset + 30 + name_len + size];
+            assert_eq!(crc32(contents), crc, "{name}");
+
+            entries.push((String::from(name), contents.to_vec()));
+            offset += 30 + name_len + size;
+        }
+
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "x.npy",
+                "state.npy",
+                "__llad__.labels.state.npy",
+                "__llad__.sample_rate.npy",
+                "__llad__.first_sample.npy",
+                "__llad__.truncated.npy",
+            ]
+        );
+        assert_eq!(entries[0].1, column_array(data.get("x").unwrap()));
+        assert_eq!(entries[1].1, column_array(data.get("state").unwrap()));
+        assert_eq!(
+            entries[2].1,
+            string_array(&[String::from("idle"), String::from("run")], Some(2))
+        );
+
+        // The central directory starts right after the entries, and the end record points at it.
+        assert_eq!(u32_at(&bytes, offset), 0x0201_4b50);
+        let end = bytes.len() - 22;
+        assert_eq!(u32_at(&bytes, end), 0x0605_4b50);
+        assert_eq!(u16_at(&bytes, end + 10), entries.len());
+        assert_eq!(u32_at(&bytes, end + 12) as usize, end - offset);
+        assert_eq!(u32_at(&bytes, end + 16) as usize, offset);
+    }
+}
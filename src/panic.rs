@@ -0,0 +1,112 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Once, TryLockError, Weak,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{meta, LladError};
+
+/// How long the panic hook waits for every writer thread to write the frames it has received.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often the panic hook checks whether the writer threads are done.
+const FLUSH_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The writer threads of all streaming loggers, which the panic hook asks to flush.
+static WRITERS: Mutex<Vec<Weak<EmergencyFlush>>> = Mutex::new(Vec::new());
+
+/// Makes sure the panic hook is only installed once.
+static INSTALL: Once = Once::new();
+
+/// Shared between the panic hook and the writer thread of a streaming logger, so that the hook can have the writer
+/// write everything it has received and mark the file as truncated before the process goes down.
+///
+/// # Fields
+///
+/// * `requested`: Set by the panic hook, after which the writer thread writes what it has and stops.
+/// * `done`: Set by the writer thread once it has written everything and marked the file as truncated.
+#[derive(Default)]
+pub(crate) struct EmergencyFlush {
+    pub requested: AtomicBool,
+    pub done: AtomicBool,
+}
+
+impl EmergencyFlush {
+    /// Creates the shared state for a new writer thread and registers it with the panic hook.
+    ///
+    /// # Returns
+    ///
+    /// * `Arc<EmergencyFlush>`: The state to hand to the writer thread.
+    pub fn register() -> Arc<Self> {
+        let flush = Arc::new(Self::default());
+        let mut writers = WRITERS.lock().unwrap_or_else(|error| error.into_inner());
+        writers.retain(|writer| writer.strong_count() > 0);
+        writers.push(Arc::downgrade(&flush));
+
+        flush
+    }
+}
+
+/// Installs the panic hook, see [`crate::SampleLogger::install_panic_hook`]. The previous hook still runs afterwards.
+pub(crate) fn install() {
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            flush_writers();
+            previous(info);
+        }));
+    });
+}
+
+/// Asks every writer thread to write what it has received, and waits until they are done or the timeout has passed.
+fn flush_writers() {
+    let writers: Vec<Arc<EmergencyFlush>> = match WRITERS.try_lock() {
+        Ok(writers) => writers.iter().filter_map(Weak::upgrade).collect(),
+        Err(TryLockError::Poisoned(error)) => error
+            .into_inner()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect(),
+        // Panicked while registering a writer, don't deadlock on the lock this thread may be holding.
+        Err(TryLockError::WouldBlock) => return,
+    };
+
+    for writer in &writers {
+        writer.requested.store(true, Ordering::Release);
+    }
+
+    let start = Instant::now();
+    while start.elapsed() < FLUSH_TIMEOUT
+        && !writers
+            .iter()
+            .all(|writer| writer.done.load(Ordering::Acquire))
+    {
+        thread::sleep(FLUSH_POLL_INTERVAL);
+    }
+}
+
+/// Marks the CSV of a streaming logger as truncated by appending a record to its sidecar file, see
+/// [`crate::Metadata::truncated`].
+///
+/// # Arguments
+///
+/// * `filename`: The name of the CSV.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the record is appended, or the error while appending it.
+pub(crate) fn mark_truncated(filename: &str) -> Result<(), LladError> {
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(meta::meta_file(filename))?;
+    writeln!(file, "truncated,true")?;
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use nih_plug::prelude::*;
+
+use crate::{Column, ColumnType, LladError, SampleLogger, Value};
+
+/// Logs the values of the parameters of a nih-plug plugin every sample, found by walking its `Params::param_map`.
+/// Every parameter gets a column named after its ID with its plain value. Float and integer parameters also get a
+/// column `<id>.smoothed` with the value their smoother returned last, enum parameters are logged with their variant
+/// names, see [`ColumnType::Enum`].
+///
+/// Create it before processing starts, e.g. in `Default::default` or `Plugin::initialize`, and call [`ParamProbe::write`] for every sample after the smoothers have
+/// been advanced for that sample:
+///
+/// ```ignore
+/// let gain = self.params.gain.smoothed.next();
+/// self.param_probe.write(&mut self.logger)?;
+/// ```
+///
+/// # Fields
+///
+/// * `_params`: The parameters, kept alive for as long as the pointers into them are used.
+/// * `columns`: The columns of every logged parameter.
+pub struct ParamProbe {
+    _params: Arc<dyn Params>,
+    columns: Vec<ParamColumns>,
+}
+
+/// The columns that a single parameter is logged to.
+///
+/// # Fields
+///
+/// * `param`: The pointer to the parameter.
+/// * `plain`: The column of the plain value of the parameter.
+/// * `smoothed`: The column of the smoothed value, for parameters that have a smoother.
+struct ParamColumns {
+    param: ParamPtr,
+    plain: Column,
+    smoothed: Option<Column>,
+}
+
+impl ParamProbe {
+    /// Registers columns for every parameter of a plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `params`: The parameters of the plugin, as returned by `Plugin::params`.
+    /// * `logger`: The logger to register the columns with.
+    ///
+    /// # Returns
+    ///
+    /// * `ParamProbe`: The probe that logs all parameters.
+    pub fn new(params: Arc<dyn Params>, logger: &mut SampleLogger) -> Self {
+        let selected = params.param_map();
+        Self::register(params, selected, logger)
+    }
+
+    /// Registers columns for a selection of the parameters of a plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `params`: The parameters of the plugin, as returned by `Plugin::params`.
+    /// * `logger`: The logger to register the columns with.
+    /// * `ids`: The IDs of the parameters to log, as in their `#[id = "..."]` attribute. The columns are registered in
+    ///   this order.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ParamProbe, LladError>`: The probe that logs the selected parameters, or
+    ///   [`LladError::UnknownParameter`] if the plugin has no parameter with one of the IDs.
+    pub fn with_ids(
+        params: Arc<dyn Params>,
+        logger: &mut SampleLogger,
+        ids: &[&str],
+    ) -> Result<Self, LladError> {
+        let mut param_map = params.param_map();
+        let mut selected = Vec::with_capacity(ids.len());
+
+        for &id in ids {
+            match param_map.iter().position(|(param_id, _, _)| param_id == id) {
+                Some(index) => selected.push(param_map.swap_remove(index)),
+                None => return Err(LladError::UnknownParameter(String::from(id))),
+            }
+        }
+
+        Ok(Self::register(params, selected, logger))
+    }
+
+    /// Registers the columns of the selected parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `params`: The parameters of the plugin.
+    /// * `selected`: The entries of the parameter map of the parameters to log.
+    /// * `logger`: The logger to register the columns with.
+    ///
+    /// # Returns
+    ///
+    /// * `ParamProbe`: The probe that logs the selected parameters.
+    fn register(
+        params: Arc<dyn Params>,
+        selected: Vec<(String, ParamPtr, String)>,
+        logger: &mut SampleLogger,
+    ) -> Self {
+        let columns = selected
+            .into_iter()
+            .map(|(id, param, _)| {
+                let smoothed_key = format!("{id}.smoothed");
+
+                let (plain, smoothed) = match param {
+                    ParamPtr::FloatParam(_) => (
+                        logger.register_typed(&id, ColumnType::F32),
+                        Some(logger.register_typed(&smoothed_key, ColumnType::F32)),
+                    ),
+                    ParamPtr::IntParam(_) => (
+                        logger.register_typed(&id, ColumnType::I64),
+                        Some(logger.register_typed(&smoothed_key, ColumnType::I64)),
+                    ),
+                    ParamPtr::BoolParam(_) => (logger.register_typed(&id, ColumnType::Bool), None),
+                    ParamPtr::EnumParam(_) => (logger.register_typed(&id, enum_type(param)), None),
+                };
+
+                ParamColumns {
+                    param,
+                    plain,
+                    smoothed,
+                }
+            })
+            .collect();
+
+        Self {
+            _params: params,
+            columns,
+        }
+    }
+
+    /// Logs the plain and smoothed value of every parameter in the current frame. Never allocates.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger`: The logger the columns were registered with.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if all values are logged or if logging is disabled, or the first
+    ///   error of [`SampleLogger::write_column`].
+    pub fn write(&self, logger: &mut SampleLogger) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            for columns in &self.columns {
+                // SAFETY: The pointers point into `self._params`, which lives as long as `self`.
+                let (plain, smoothed) = unsafe {
+                    match columns.param {
+                        ParamPtr::FloatParam(p) => (
+                            Value::F32((*p).modulated_plain_value()),
+                            Some(Value::F32((*p).smoothed.previous_value())),
+                        ),
+                        ParamPtr::IntParam(p) => (
+                            Value::I64((*p).modulated_plain_value() as i64),
+                            Some(Value::I64((*p).smoothed.previous_value() as i64)),
+                        ),
+                        ParamPtr::BoolParam(p) => (Value::Bool((*p).modulated_plain_value()), None),
+                        ParamPtr::EnumParam(p) => {
+                            (Value::Enum((*p).modulated_plain_value() as u32), None)
+                        }
+                    }
+                };
+
+                logger.write_column(columns.plain, plain)?;
+                if let (Some(column), Some(value)) = (columns.smoothed, smoothed) {
+                    logger.write_column(column, value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates the type of the column of an enum parameter, with the name of every variant as its label.
+///
+/// # Arguments
+///
+/// * `param`: The pointer to the enum parameter.
+///
+/// # Returns
+///
+/// * `ColumnType`: The [`ColumnType::Enum`] of the parameter.
+fn enum_type(param: ParamPtr) -> ColumnType {
+    // SAFETY: Only called while the parameters that `param` points into are alive.
+    unsafe {
+        let steps = param.step_count().unwrap_or(0);
+        ColumnType::Enum(
+            (0..=steps)
+                .map(|index| {
+                    param.normalized_value_to_string(param.preview_normalized(index as f32), false)
+                })
+                .collect(),
+        )
+    }
+}
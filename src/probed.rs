@@ -0,0 +1,360 @@
+use std::sync::Arc;
+
+use nih_plug::prelude::*;
+
+use crate::{runner::channel_count, LladError, Logged, Probe, SampleLogger};
+
+/// Wraps a nih-plug [`Plugin`] and logs every channel of its main input before `process`, and of its main output
+/// after it, to the [`SampleLogger`] of the plugin. The channels are logged as the probes `input` and `output`, so the
+/// CSV gets the columns `input[0]`, `output[0]` and so on, next to the columns the plugin logs itself.
+///
+/// The wrapped plugin keeps calling [`SampleLogger::write_column`] and [`SampleLogger::end_frame`] for every sample as
+/// usual. `process` is wrapped in a block, see [`SampleLogger::begin_block`], so that its frames line up with the input
+/// and output of the same samples. The buffers are logged too, see [`SampleLogger::log_buffers`], so the CSV shows
+/// where every call to `process` began, and the sample rate is recorded with [`SampleLogger::set_buffer_config`].
+/// Columns that the plugin creates with [`SampleLogger::write`] in the first buffer are added to the block, see
+/// [`SampleLogger::register_typed`]. Like without the wrapper, keys that are first written to after that are rejected.
+///
+/// Every note event the wrapped plugin receives through `ProcessContext::next_event` is logged to the event log as
+/// well, see [`SampleLogger::note_event`].
+///
+/// This is meant to be run by a [`crate::Runner`], e.g. with `llad::run_cli::<Probed<MyPlugin>>()`. The wrapper has
+/// no editor, and auxiliary inputs and outputs are passed through without being logged. Main input channels beyond
+/// the number of main output channels are not in the buffer nih-plug passes to `process`, so they are not logged.
+///
+/// # Fields
+///
+/// * `plugin`: The wrapped plugin.
+/// * `input`: The probe of the main input, registered in `initialize`.
+/// * `output`: The probe of the main output, registered in `initialize`.
+/// * `input_channels`: The number of channels of the main input that are in the buffer, which has as many channels as
+///   the main output.
+/// * `received`: The note events the plugin received in the current buffer, logged after `process`.
+/// * `dropped_events`: The number of note events that did not fit in the event log of the logger.
+pub struct Probed<P: Plugin + Logged> {
+    plugin: P,
+    input: Option<Probe>,
+    output: Option<Probe>,
+    input_channels: usize,
+    received: Vec<PluginNoteEvent<P>>,
+    dropped_events: u64,
+}
+
+/// The number of note events per buffer that [`Probed`] reserves space for. Further events in the same buffer are
+/// passed to the plugin but not logged, so that `process` never allocates.
+///
+/// The event log of the logger gets space for this many events in total, not per buffer. A logger created with
+/// [`SampleLogger::with_capacity`] can't grow its event log, so for longer runs reserve space for more events with
+/// [`SampleLogger::reserve_events`]. Events that don't fit are dropped and counted, see [`Probed::dropped_events`].
+const MAX_NOTE_EVENTS: usize = 1024;
+
+impl<P: Plugin + Logged> Default for Probed<P> {
+    fn default() -> Self {
+        Self::new(P::default())
+    }
+}
+
+impl<P: Plugin + Logged> Probed<P> {
+    /// Wraps a plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin`: The plugin whose input and output should be logged.
+    ///
+    /// # Returns
+    ///
+    /// * `Probed<P>`: The wrapped plugin.
+    pub fn new(plugin: P) -> Self {
+        Self {
+            plugin,
+            input: None,
+            output: None,
+            input_channels: 0,
+            received: Vec::new(),
+            dropped_events: 0,
+        }
+    }
+
+    /// Returns the wrapped plugin.
+    pub fn inner(&mut self) -> &mut P {
+        &mut self.plugin
+    }
+
+    /// Returns the number of note events that were passed to the plugin but not logged, because the event log of a
+    /// logger created with [`SampleLogger::with_capacity`] was full, see [`SampleLogger::reserve_events`].
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped_events
+    }
+
+    /// Starts a block for the buffer and logs the main input of every frame in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer`: The main buffer, before it is processed.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the input is logged, or the error of the logger, in which case
+    ///   the block is discarded.
+    fn log_input(&mut self, buffer: &Buffer) -> Result<(), LladError> {
+        let Some(input) = self.input else {
+            return Ok(());
+        };
+        let input_channels = self.input_channels;
+        let logger = self.plugin.sample_logger();
+
+        logger.begin_block(buffer.samples())?;
+        if let Err(error) = log_probe(logger, input, buffer, input_channels) {
+            // The plugin doesn't process a buffer whose input could not be logged, so none of its frames are logged.
+            logger.discard_block();
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Logs the main output of every frame in the buffer and the note events the plugin received, and ends the block.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer`: The main buffer, after it is processed.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the output is logged, or the error of the logger. The block is
+    ///   ended or discarded either way, so that the next buffer can be logged. Note events that don't fit in the event
+    ///   log are counted in `dropped_events` instead of being an error.
+    fn log_output(&mut self, buffer: &Buffer) -> Result<(), LladError> {
+        let Some(output) = self.output else {
+            return Ok(());
+        };
+        let logger = self.plugin.sample_logger();
+
+        if let Err(error) = log_probe(logger, output, buffer, usize::MAX) {
+            logger.discard_block();
+            return Err(error);
+        }
+
+        for event in self.received.drain(..) {
+            // Only fails if the event log of a preallocated logger is full, which is no reason to stop processing.
+            if logger.note_event(&event).is_err() {
+                self.dropped_events += 1;
+            }
+        }
+
+        logger.end_block()
+    }
+}
+
+/// Logs the channels of a buffer to a probe in the current block.
+///
+/// # Arguments
+///
+/// * `logger`: The logger, in which a block is started.
+/// * `probe`: The probe to log the channels to.
+/// * `buffer`: The buffer.
+/// * `channels`: The maximum number of channels to log.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the channels are logged, or the error of the logger.
+fn log_probe(
+    logger: &mut SampleLogger,
+    probe: Probe,
+    buffer: &Buffer,
+    channels: usize,
+) -> Result<(), LladError> {
+    for (channel, samples) in buffer
+        .as_slice_immutable()
+        .iter()
+        .take(channels)
+        .enumerate()
+    {
+        for (offset, &sample) in samples.iter().enumerate() {
+            logger.write_probe_at(probe, channel, offset, sample)?;
+        }
+    }
+
+    Ok(())
+}
+
+impl<P: Plugin + Logged> Logged for Probed<P> {
+    fn sample_logger(&mut self) -> &mut SampleLogger {
+        self.plugin.sample_logger()
+    }
+}
+
+impl<P: Plugin + Logged> Plugin for Probed<P> {
+    const NAME: &'static str = P::NAME;
+    const VENDOR: &'static str = P::VENDOR;
+    const URL: &'static str = P::URL;
+    const EMAIL: &'static str = P::EMAIL;
+    const VERSION: &'static str = P::VERSION;
+
+    const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = P::AUDIO_IO_LAYOUTS;
+    const MIDI_INPUT: MidiConfig = P::MIDI_INPUT;
+    const MIDI_OUTPUT: MidiConfig = P::MIDI_OUTPUT;
+    const SAMPLE_ACCURATE_AUTOMATION: bool = P::SAMPLE_ACCURATE_AUTOMATION;
+    const HARD_REALTIME_ONLY: bool = P::HARD_REALTIME_ONLY;
+
+    type SysExMessage = P::SysExMessage;
+    type BackgroundTask = P::BackgroundTask;
+
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        self.plugin.task_executor()
+    }
+
+    fn params(&self) -> Arc<dyn Params> {
+        self.plugin.params()
+    }
+
+    fn filter_state(state: &mut PluginState) {
+        P::filter_state(state)
+    }
+
+    fn initialize(
+        &mut self,
+        audio_io_layout: &AudioIOLayout,
+        buffer_config: &BufferConfig,
+        context: &mut impl InitContext<Self>,
+    ) -> bool {
+        // Registered before the plugin initializes, which may start streaming after registering its own columns.
+        let output_channels = channel_count(audio_io_layout.main_output_channels);
+        // The buffer has a channel for every output, input channels beyond those are not passed to the plugin.
+        self.input_channels =
+            channel_count(audio_io_layout.main_input_channels).min(output_channels);
+        let logger = self.plugin.sample_logger();
+        logger.log_buffers();
+        logger.set_buffer_config::<P>(buffer_config);
+        self.input = Some(logger.register_probe("input", self.input_channels));
+        self.output = Some(logger.register_probe("output", output_channels));
+        // A preallocated logger can't log more events than reserved, see `SampleLogger::reserve_events`.
+        logger.reserve_events(MAX_NOTE_EVENTS);
+
+        self.received.reserve_exact(MAX_NOTE_EVENTS);
+
+        let initialized = self.plugin.initialize(
+            audio_io_layout,
+            buffer_config,
+            &mut InnerContext {
+                context,
+                received: None,
+            },
+        );
+
+        self.plugin
+            .sample_logger()
+            .reserve_block(buffer_config.max_buffer_size as usize);
+
+        initialized
+    }
+
+    fn reset(&mut self) {
+        self.plugin.reset()
+    }
+
+    fn process(
+        &mut self,
+        buffer: &mut Buffer,
+        aux: &mut AuxiliaryBuffers,
+        context: &mut impl ProcessContext<Self>,
+    ) -> ProcessStatus {
+        self.received.clear();
+
+        if !cfg!(feature = "disabled") && self.log_input(buffer).is_err() {
+            return ProcessStatus::Error("LLAD failed to log the input.");
+        }
+
+        let status = self.plugin.process(
+            buffer,
+            aux,
+            &mut InnerContext {
+                context,
+                received: Some(&mut self.received),
+            },
+        );
+
+        if !cfg!(feature = "disabled") && self.log_output(buffer).is_err() {
+            return ProcessStatus::Error("LLAD failed to log the output.");
+        }
+
+        status
+    }
+
+    fn deactivate(&mut self) {
+        self.plugin.deactivate()
+    }
+}
+
+/// Passes the contexts of the wrapper on to the wrapped plugin, which share their background task and SysEx types.
+///
+/// # Fields
+///
+/// * `context`: The context of the wrapper.
+/// * `received`: Where to keep the note events the plugin receives, or `None` if they are not logged.
+struct InnerContext<'a, C, P: Plugin> {
+    context: &'a mut C,
+    received: Option<&'a mut Vec<PluginNoteEvent<P>>>,
+}
+
+impl<P: Plugin + Logged, C: InitContext<Probed<P>>> InitContext<P> for InnerContext<'_, C, P> {
+    fn plugin_api(&self) -> PluginApi {
+        self.context.plugin_api()
+    }
+
+    fn execute(&self, task: P::BackgroundTask) {
+        self.context.execute(task)
+    }
+
+    fn set_latency_samples(&self, samples: u32) {
+        self.context.set_latency_samples(samples)
+    }
+
+    fn set_current_voice_capacity(&self, capacity: u32) {
+        self.context.set_current_voice_capacity(capacity)
+    }
+}
+
+impl<P: Plugin + Logged, C: ProcessContext<Probed<P>>> ProcessContext<P>
+    for InnerContext<'_, C, P>
+{
+    fn plugin_api(&self) -> PluginApi {
+        self.context.plugin_api()
+    }
+
+    fn execute_background(&self, task: P::BackgroundTask) {
+        self.context.execute_background(task)
+    }
+
+    fn execute_gui(&self, task: P::BackgroundTask) {
+        self.context.execute_gui(task)
+    }
+
+    fn transport(&self) -> &Transport {
+        self.context.transport()
+    }
+
+    fn next_event(&mut self) -> Option<PluginNoteEvent<P>> {
+        let event = self.context.next_event()?;
+
+        if let Some(received) = &mut self.received {
+            if received.len() < received.capacity() {
+                received.push(event.clone());
+            }
+        }
+
+        Some(event)
+    }
+
+    fn send_event(&mut self, event: PluginNoteEvent<P>) {
+        self.context.send_event(event)
+    }
+
+    fn set_latency_samples(&self, samples: u32) {
+        self.context.set_latency_samples(samples)
+    }
+
+    fn set_current_voice_capacity(&self, capacity: u32) {
+        self.context.set_current_voice_capacity(capacity)
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    fs::File,
+    ops::{Bound, RangeBounds},
+    sync::Arc,
+};
+
+use crate::LladError;
+
+/// A value that was written to a column outside of the range set with [`crate::SampleLogger::set_range`], as returned
+/// by [`crate::SampleLogger::violations`] and [`read_violations`].
+///
+/// # Fields
+///
+/// * `column`: The key of the column.
+/// * `sample`: The index of the frame in which the value was written, counted from the first frame the logger saw.
+/// * `value`: The value, converted as in [`crate::Value::to_f64`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub column: String,
+    pub sample: u64,
+    pub value: f64,
+}
+
+/// The range of values a column may have, see [`crate::SampleLogger::set_range`].
+///
+/// # Fields
+///
+/// * `start`: The lower bound of the range.
+/// * `end`: The upper bound of the range.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Range {
+    start: Bound<f64>,
+    end: Bound<f64>,
+}
+
+impl Range {
+    /// Copies the bounds of a range.
+    ///
+    /// # Arguments
+    ///
+    /// * `range`: Any range of floats, e.g. `-60.0..=0.0` or `0.0..`.
+    ///
+    /// # Returns
+    ///
+    /// * `Range`: The range with the same bounds.
+    pub fn new(range: impl RangeBounds<f64>) -> Self {
+        Self {
+            start: range.start_bound().cloned(),
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    /// Returns `true` if the value is in the range. NaN is in no range.
+    pub fn contains(&self, value: f64) -> bool {
+        // An unbounded range would contain NaN, as no bound is compared with it.
+        !value.is_nan() && (self.start, self.end).contains(&value)
+    }
+}
+
+/// A violation as it is kept by the logger until it is written, with the index of the column instead of its key so
+/// that recording it never allocates.
+///
+/// # Fields
+///
+/// * `column`: The index of the column.
+/// * `sample`: The index of the frame in which the value was written.
+/// * `value`: The value.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Recorded {
+    pub column: usize,
+    pub sample: u64,
+    pub value: f64,
+}
+
+/// Returns the name of the file with the range violations of a CSV.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the CSV.
+///
+/// # Returns
+///
+/// * `String`: The name of the file, the name of the CSV with `.violations.csv` appended.
+pub(crate) fn violations_file(filename: &str) -> String {
+    format!("{filename}.violations.csv")
+}
+
+/// Writes the range violations of a CSV to a CSV with the columns `sample`, `column` and `value`.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the CSV.
+/// * `violations`: The violations, in the order in which they were written.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the violations are written, or the error while writing them.
+pub(crate) fn write_violations(filename: &str, violations: &[Violation]) -> Result<(), LladError> {
+    let mut writer = csv::Writer::from_writer(File::create(violations_file(filename))?);
+    writer.write_record(["sample", "column", "value"])?;
+
+    for violation in violations {
+        writer.write_record([
+            violation.sample.to_string().as_str(),
+            violation.column.as_str(),
+            violation.value.to_string().as_str(),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the range violations that [`crate::SampleLogger`] writes next to a CSV, see
+/// [`crate::SampleLogger::set_range`].
+///
+/// # Arguments
+///
+/// * `filename` - A string representing the path to the CSV file, not to the file with the violations itself.
+///
+/// # Returns
+///
+/// * `Result<Vec<Violation>, LladError>` - On success, returns every violation in the order in which they were
+///   written, which is empty if the CSV has no file with violations. On failure, returns an error.
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * The file with the violations exists but cannot be opened, as [`LladError::Io`].
+/// * There is an error reading the records, as [`LladError::Csv`].
+/// * A sample or value can't be parsed, as [`LladError::Parse`] with the column, row and field.
+pub fn read_violations(filename: String) -> Result<Vec<Violation>, LladError> {
+    let file = match File::open(violations_file(filename.as_str())) {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut reader = csv::Reader::from_reader(file);
+    let mut violations = Vec::new();
+
+    for (row, record) in reader.records().enumerate() {
+        let record = record?;
+        let field = |index: usize| record.get(index).unwrap_or_default();
+        let parse_error = |column: &str, value: &str| LladError::Parse {
+            column: Arc::from(column),
+            row,
+            value: String::from(value),
+        };
+
+        violations.push(Violation {
+            sample: field(0)
+                .parse()
+                .map_err(|_| parse_error("sample", field(0)))?,
+            column: String::from(field(1)),
+            value: field(2)
+                .parse()
+                .map_err(|_| parse_error("value", field(2)))?,
+        });
+    }
+
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SampleLogger;
+
+    #[test]
+    fn bounds_are_kept() {
+        assert!(Range::new(-60.0..=0.0).contains(0.0));
+        assert!(!Range::new(-60.0..0.0).contains(0.0));
+        assert!(Range::new(0.0..).contains(f64::INFINITY));
+        assert!(!Range::new(0.0..).contains(-f64::MIN_POSITIVE));
+        assert!(!Range::new(..).contains(f64::NAN));
+    }
+
+    #[test]
+    fn violations_are_recorded_and_read_back() {
+        let filename = std::env::temp_dir().join("llad_range.csv");
+        let filename = filename.to_string_lossy().into_owned();
+
+        let mut logger = SampleLogger::new(filename.clone());
+        let gain = logger.register("gain");
+        logger.set_range(gain, -60.0..=0.0).unwrap();
+        for value in [-6.0_f32, 1.0, -60.0, -70.0, f32::NAN] {
+            logger.write_column(gain, value).unwrap();
+            logger.end_frame().unwrap();
+        }
+
+        let violations = logger.violations();
+        assert_eq!(violations.len(), 3);
+        assert_eq!(
+            violations[..2],
+            [
+                Violation {
+                    column: String::from("gain"),
+                    sample: 1,
+                    value: 1.0,
+                },
+                Violation {
+                    column: String::from("gain"),
+                    sample: 3,
+                    value: -70.0,
+                },
+            ]
+        );
+        assert_eq!(violations[2].sample, 4);
+        assert!(violations[2].value.is_nan());
+
+        logger.write_debug_values().unwrap();
+        let read = read_violations(filename).unwrap();
+        assert_eq!(read[..2], violations[..2]);
+        assert!(read[2].value.is_nan());
+    }
+
+    #[test]
+    fn errors_still_log_the_value() {
+        let filename = std::env::temp_dir().join("llad_range_errors.csv");
+        let mut logger = SampleLogger::new(filename.to_string_lossy().into_owned());
+        let gain = logger.register("gain");
+        logger.set_range(gain, -60.0..=0.0).unwrap();
+        logger.set_range_errors(true);
+
+        assert!(matches!(
+            logger.write_column(gain, -70.0_f32),
+            Err(LladError::OutOfRange { sample: 0, .. })
+        ));
+        logger.end_frame().unwrap();
+        logger.write_column(gain, -6.0_f32).unwrap();
+        logger.end_frame().unwrap();
+
+        let values = logger.debug_values().get("gain").unwrap();
+        assert_eq!(values.as_f32(), Some(&[-70.0, -6.0][..]));
+        assert_eq!(logger.violations().len(), 1);
+    }
+}
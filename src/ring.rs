@@ -0,0 +1,195 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    Arc,
+};
+
+/// The memory shared between a [`FrameProducer`] and a [`FrameConsumer`]. Values are stored as their bits in atomics so
+/// that neither side needs a lock or unsafe code to access a slot.
+///
+/// # Fields
+///
+/// * `slots`: The values of all frames in the ring, `frame_size` values per frame.
+/// * `frame_size`: The number of values in a frame.
+/// * `capacity`: The number of frames that fit in the ring.
+/// * `read`: The total number of frames that have been popped, only written by the consumer.
+/// * `written`: The total number of frames that have been pushed, only written by the producer.
+/// * `closed`: Set by the producer when no more frames will be pushed.
+struct Shared {
+    slots: Box<[AtomicU64]>,
+    frame_size: usize,
+    capacity: usize,
+    read: AtomicUsize,
+    written: AtomicUsize,
+    closed: AtomicBool,
+}
+
+/// The audio thread side of a single producer single consumer ring buffer of fixed-size frames.
+pub(crate) struct FrameProducer {
+    shared: Arc<Shared>,
+}
+
+/// The writer thread side of a single producer single consumer ring buffer of fixed-size frames.
+pub(crate) struct FrameConsumer {
+    shared: Arc<Shared>,
+}
+
+/// Creates a lock-free ring buffer that can hold `capacity` frames of `frame_size` values, each
+/// stored as 64 bits. All memory is allocated here,
+/// pushing and popping frames never allocates.
+///
+/// # Arguments
+///
+/// * `frame_size`: The number of values in every frame.
+/// * `capacity`: The number of frames the ring buffer can hold before the producer has to wait for the consumer.
+///
+/// # Returns
+///
+/// * `(FrameProducer, FrameConsumer)`: The two ends of the ring buffer, which can be moved to different threads.
+pub(crate) fn frame_ring(frame_size: usize, capacity: usize) -> (FrameProducer, FrameConsumer) {
+    let shared = Arc::new(Shared {
+        slots: (0..frame_size * capacity)
+            .map(|_| AtomicU64::new(0))
+            .collect(),
+        frame_size,
+        capacity,
+        read: AtomicUsize::new(0),
+        written: AtomicUsize::new(0),
+        closed: AtomicBool::new(false),
+    });
+
+    (
+        FrameProducer {
+            shared: shared.clone(),
+        },
+        FrameConsumer { shared },
+    )
+}
+
+impl FrameProducer {
+    /// Copies a frame into the ring buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`: The bits of the values of the frame, must be exactly `frame_size` long.
+    ///
+    /// # Returns
+    ///
+    /// * `bool`: `true` if the frame was pushed, `false` if the ring buffer is full and the frame was not pushed.
+    pub fn push(&self, frame: &[u64]) -> bool {
+        let shared = &self.shared;
+        debug_assert_eq!(frame.len(), shared.frame_size);
+
+        let written = shared.written.load(Ordering::Relaxed);
+        if written - shared.read.load(Ordering::Acquire) == shared.capacity {
+            return false;
+        }
+
+        let start = (written % shared.capacity) * shared.frame_size;
+        for (slot, value) in shared.slots[start..start + shared.frame_size]
+            .iter()
+            .zip(frame)
+        {
+            slot.store(*value, Ordering::Relaxed);
+        }
+
+        // Publishes the stores above to the consumer.
+        shared.written.store(written + 1, Ordering::Release);
+        true
+    }
+
+    /// Tells the consumer that no more frames will be pushed, so it can stop once the ring buffer is drained.
+    pub fn close(&self) {
+        self.shared.closed.store(true, Ordering::Release);
+    }
+}
+
+impl FrameConsumer {
+    /// Copies the oldest frame out of the ring buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`: The buffer to copy the frame into, must be exactly `frame_size` long.
+    ///
+    /// # Returns
+    ///
+    /// * `bool`: `true` if a frame was popped into `frame`, `false` if the ring buffer is empty.
+    pub fn pop(&self, frame: &mut [u64]) -> bool {
+        let shared = &self.shared;
+        debug_assert_eq!(frame.len(), shared.frame_size);
+
+        let read = shared.read.load(Ordering::Relaxed);
+        if read == shared.written.load(Ordering::Acquire) {
+            return false;
+        }
+
+        let start = (read % shared.capacity) * shared.frame_size;
+        for (value, slot) in frame
+            .iter_mut()
+            .zip(&shared.slots[start..start + shared.frame_size])
+        {
+            *value = slot.load(Ordering::Relaxed);
+        }
+
+        // Hands the slot back to the producer once the values have been read.
+        shared.read.store(read + 1, Ordering::Release);
+        true
+    }
+
+    /// Returns `true` if the producer has closed the ring buffer. Frames pushed before closing may still be waiting to be
+    /// popped, so pop until empty after this returns `true`.
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_wrap_around_the_ring() {
+        let (producer, consumer) = frame_ring(2, 3);
+        let mut frame = [0; 2];
+
+        for i in 0..10 {
+            assert!(producer.push(&[i, i * 10]));
+            assert!(producer.push(&[i + 100, i * 10 + 100]));
+            assert!(consumer.pop(&mut frame));
+            assert_eq!(frame, [i, i * 10]);
+            assert!(consumer.pop(&mut frame));
+            assert_eq!(frame, [i + 100, i * 10 + 100]);
+        }
+        assert!(!consumer.pop(&mut frame));
+    }
+
+    #[test]
+    fn full_ring_rejects_frames() {
+        let (producer, consumer) = frame_ring(1, 2);
+        let mut frame = [0];
+
+        assert!(producer.push(&[1]));
+        assert!(producer.push(&[2]));
+        assert!(!producer.push(&[3]));
+
+        assert!(consumer.pop(&mut frame));
+        assert_eq!(frame, [1]);
+        assert!(producer.push(&[4]));
+        assert!(consumer.pop(&mut frame));
+        assert_eq!(frame, [2]);
+        assert!(consumer.pop(&mut frame));
+        assert_eq!(frame, [4]);
+        assert!(!consumer.pop(&mut frame));
+    }
+
+    #[test]
+    fn zero_capacity_never_holds_a_frame() {
+        let (producer, consumer) = frame_ring(2, 0);
+        let mut frame = [0; 2];
+
+        assert!(!producer.push(&[1, 2]));
+        assert!(!consumer.pop(&mut frame));
+
+        producer.close();
+        assert!(consumer.is_closed());
+    }
+}
@@ -0,0 +1,437 @@
+use std::{num::NonZeroU32, slice::ChunksMut};
+
+use nih_plug::prelude::*;
+
+use crate::{
+    wav::{read_wav, write_wav},
+    LladError, SampleLogger,
+};
+
+/// Implemented by plugins that own a [`SampleLogger`], so that a [`Runner`] can flush it once the whole input has been
+/// processed.
+pub trait Logged {
+    /// Returns the logger that the plugin writes its debug values to.
+    fn sample_logger(&mut self) -> &mut SampleLogger;
+}
+
+/// Runs a nih-plug [`Plugin`] over a WAV file without a host, calling `initialize`, `reset` and `process` the way a host
+/// would and writing the processed audio to another WAV file. This is the light weight host that LLAD is meant to be
+/// used with: it runs the plugin exactly as long as the input file, and [`Runner::run_logged`] writes the
+/// [`SampleLogger`] of the plugin once it's done.
+///
+/// The plugin gets the first layout whose main input matches the channel count of the input file. If there is none, a
+/// layout without a main input is used, such as that of a synthesizer: its main buffer starts out silent and the input
+/// file only sets the length and sample rate of the run. Auxiliary inputs are silent and auxiliary outputs are
+/// discarded. There are no note events, and background tasks are executed right away on the calling thread.
+///
+/// The transport is playing, but reports no position: nih-plug only lets its own wrappers set the position of a
+/// `Transport`, so `pos_samples` and the other positions are `None` in every buffer. Plugins that follow the song
+/// position should count the processed samples themselves when they are run offline.
+///
+/// # Fields
+///
+/// * `buffer_size`: The number of samples passed to every `process` call, except for the last one which may be shorter.
+/// * `sample_rate`: The sample rate the plugin is initialized with, or `None` to use the sample rate of the input file.
+pub struct Runner {
+    buffer_size: usize,
+    sample_rate: Option<f32>,
+}
+
+impl Default for Runner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runner {
+    /// Creates a new `Runner` with a buffer size of 512 samples that uses the sample rate of the input file.
+    ///
+    /// # Returns
+    ///
+    /// * `Runner`: The newly created `Runner` instance.
+    pub fn new() -> Self {
+        Self {
+            buffer_size: 512,
+            sample_rate: None,
+        }
+    }
+
+    /// Sets the number of samples passed to every `process` call.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer_size`: The maximum buffer size, must be at least 1.
+    pub fn set_buffer_size(&mut self, buffer_size: usize) {
+        self.buffer_size = buffer_size.max(1);
+    }
+
+    /// Sets the sample rate that the plugin is initialized with and that the output file is written with. The input is
+    /// not resampled, so this only changes how the plugin interprets the input.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate`: The sample rate in Hz.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = Some(sample_rate);
+    }
+
+    /// Runs the plugin over the input file and writes the result to the output file, then deactivates the plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin`: The plugin to run, which is initialized by this function.
+    /// * `input_file`: The path to the WAV file that is passed through the plugin.
+    /// * `output_file`: The path to the WAV file that the output of the plugin is written to, as 32-bit floats.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the whole file was processed. Returns `Err` if the files
+    ///   can't be read or written, if the plugin has no layout for the channel count of the input and no layout without
+    ///   a main input, if initialization fails or if `process` returns an error.
+    pub fn run<P: Plugin>(
+        &self,
+        plugin: &mut P,
+        input_file: &str,
+        output_file: &str,
+    ) -> Result<(), LladError> {
+        let wav = read_wav(input_file)?;
+        let input = wav.channels;
+        let sample_rate = self.sample_rate.unwrap_or(wav.sample_rate as f32);
+        let length = input.first().map(Vec::len).unwrap_or(0);
+
+        let layouts = P::AUDIO_IO_LAYOUTS;
+        let layout = layouts
+            .iter()
+            .find(|layout| channel_count(layout.main_input_channels) == input.len())
+            .or_else(|| {
+                layouts.iter().find(|layout| {
+                    layout.main_input_channels.is_none() && layout.main_output_channels.is_some()
+                })
+            })
+            .ok_or(LladError::NoMatchingLayout {
+                channels: input.len(),
+            })?;
+
+        let output_channels = channel_count(layout.main_output_channels);
+        if output_channels == 0 {
+            return Err(LladError::NoMainOutput);
+        }
+
+        let buffer_config = BufferConfig {
+            sample_rate,
+            min_buffer_size: None,
+            max_buffer_size: self.buffer_size as u32,
+            process_mode: ProcessMode::Offline,
+        };
+
+        let mut context = OfflineContext::<P>::new(plugin.task_executor(), sample_rate);
+        let params = plugin.params();
+        reset_smoothers(params.as_ref());
+
+        if !plugin.initialize(layout, &buffer_config, &mut context) {
+            return Err(LladError::InitializeFailed);
+        }
+        plugin.reset();
+
+        // The main buffer is processed in place, channels without an input start out silent.
+        let input_channels = channel_count(layout.main_input_channels);
+        let mut main: Vec<Vec<f32>> = (0..output_channels)
+            .map(|channel| match input.get(channel) {
+                Some(samples) if channel < input_channels => samples.clone(),
+                _ => vec![0.0; length],
+            })
+            .collect();
+        let mut aux_inputs = aux_storage(layout.aux_input_ports, self.buffer_size);
+        let mut aux_outputs = aux_storage(layout.aux_output_ports, self.buffer_size);
+
+        // Everything the loop needs is allocated up front, so that processing doesn't allocate.
+        {
+            let mut chunks: Vec<ChunksMut<f32>> = main
+                .iter_mut()
+                .map(|channel| channel.chunks_mut(self.buffer_size))
+                .collect();
+            let mut buffer = Buffer::default();
+            // SAFETY: No slices are set yet, this only makes room for them.
+            unsafe {
+                buffer.set_slices(0, |slices| slices.reserve_exact(chunks.len()));
+            }
+            let mut aux_input_buffers: Vec<Buffer> =
+                aux_inputs.iter_mut().map(|port| buffer_of(port)).collect();
+            let mut aux_output_buffers: Vec<Buffer> =
+                aux_outputs.iter_mut().map(|port| buffer_of(port)).collect();
+
+            for start in (0..length).step_by(self.buffer_size) {
+                let samples = (length - start).min(self.buffer_size);
+
+                // SAFETY: Every chunk has `samples` samples, as all channels are as long as the input, and is only
+                // handed out once.
+                unsafe {
+                    buffer.set_slices(samples, |slices| {
+                        slices.clear();
+                        slices.extend(chunks.iter_mut().filter_map(Iterator::next));
+                    });
+                }
+                for aux_buffer in aux_input_buffers
+                    .iter_mut()
+                    .chain(aux_output_buffers.iter_mut())
+                {
+                    shrink(aux_buffer, samples);
+                }
+                for aux_buffer in aux_input_buffers.iter_mut() {
+                    for channel in aux_buffer.as_slice() {
+                        channel.fill(0.0);
+                    }
+                }
+
+                let mut aux = AuxiliaryBuffers {
+                    inputs: &mut aux_input_buffers,
+                    outputs: &mut aux_output_buffers,
+                };
+
+                if let ProcessStatus::Error(message) =
+                    plugin.process(&mut buffer, &mut aux, &mut context)
+                {
+                    return Err(LladError::Process(message));
+                }
+            }
+        }
+
+        plugin.deactivate();
+        write_wav(output_file, &main, sample_rate as u32)
+    }
+
+    /// Runs the plugin like [`Runner::run`] and writes the debug values of its [`SampleLogger`] afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin`: The plugin to run, which is initialized by this function.
+    /// * `input_file`: The path to the WAV file that is passed through the plugin.
+    /// * `output_file`: The path to the WAV file that the output of the plugin is written to, as 32-bit floats.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the whole file was processed and the debug values were
+    ///   written. Returns `Err` for the same reasons as [`Runner::run`] or if writing the debug values fails.
+    pub fn run_logged<P: Plugin + Logged>(
+        &self,
+        plugin: &mut P,
+        input_file: &str,
+        output_file: &str,
+    ) -> Result<(), LladError> {
+        self.run(plugin, input_file, output_file)?;
+        plugin.sample_logger().write_debug_values()
+    }
+}
+
+/// Runs a plugin with the arguments of the current process, so a plugin crate can get an offline runner binary with a
+/// one line `main`:
+///
+/// ```ignore
+/// fn main() -> Result<(), llad::LladError> {
+///     llad::run_cli::<MyPlugin>()
+/// }
+/// ```
+///
+/// The expected arguments are `<input.wav> <output.wav> [--buffer-size <samples>] [--sample-rate <hz>]`. The
+/// `llad-gain` binary of this crate, in `src/bin/llad-gain.rs`, is a complete example.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the plugin ran and its debug values were written, or the error
+///   that occurred while parsing the arguments or running the plugin.
+pub fn run_cli<P: Plugin + Logged>() -> Result<(), LladError> {
+    const USAGE: &str =
+        "usage: <input.wav> <output.wav> [--buffer-size <samples>] [--sample-rate <hz>]";
+
+    let mut runner = Runner::new();
+    let mut files = Vec::new();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--buffer-size" => runner.set_buffer_size(parse_argument(args.next(), USAGE)?),
+            "--sample-rate" => runner.set_sample_rate(parse_argument(args.next(), USAGE)?),
+            _ => files.push(arg),
+        }
+    }
+
+    match files.as_slice() {
+        [input_file, output_file] => runner.run_logged(&mut P::default(), input_file, output_file),
+        _ => Err(LladError::Usage(USAGE)),
+    }
+}
+
+/// Parses the value of a command line option.
+///
+/// # Arguments
+///
+/// * `value`: The argument after the option, if there is one.
+/// * `usage`: The usage message to return if the value is missing or invalid.
+///
+/// # Returns
+///
+/// * `Result<T, LladError>`: The parsed value, or [`LladError::Usage`].
+fn parse_argument<T: std::str::FromStr>(
+    value: Option<String>,
+    usage: &'static str,
+) -> Result<T, LladError> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or(LladError::Usage(usage))
+}
+
+/// The host side of the plugin's contexts during an offline run. Background tasks are executed immediately.
+///
+/// # Fields
+///
+/// * `executor`: The task executor of the plugin.
+/// * `transport`: The transport information passed to `process`, which is always playing and has no position, see
+///   [`Runner`].
+struct OfflineContext<P: Plugin> {
+    executor: TaskExecutor<P>,
+    transport: Transport,
+}
+
+impl<P: Plugin> OfflineContext<P> {
+    fn new(executor: TaskExecutor<P>, sample_rate: f32) -> Self {
+        let mut transport = Transport::new(sample_rate);
+        transport.playing = true;
+
+        Self {
+            executor,
+            transport,
+        }
+    }
+}
+
+impl<P: Plugin> InitContext<P> for OfflineContext<P> {
+    fn plugin_api(&self) -> PluginApi {
+        PluginApi::Standalone
+    }
+
+    fn execute(&self, task: P::BackgroundTask) {
+        (self.executor)(task);
+    }
+
+    fn set_latency_samples(&self, _samples: u32) {}
+
+    fn set_current_voice_capacity(&self, _capacity: u32) {}
+}
+
+impl<P: Plugin> ProcessContext<P> for OfflineContext<P> {
+    fn plugin_api(&self) -> PluginApi {
+        PluginApi::Standalone
+    }
+
+    fn execute_background(&self, task: P::BackgroundTask) {
+        (self.executor)(task);
+    }
+
+    fn execute_gui(&self, task: P::BackgroundTask) {
+        (self.executor)(task);
+    }
+
+    fn transport(&self) -> &Transport {
+        &self.transport
+    }
+
+    fn next_event(&mut self) -> Option<PluginNoteEvent<P>> {
+        None
+    }
+
+    fn send_event(&mut self, _event: PluginNoteEvent<P>) {}
+
+    fn set_latency_samples(&self, _samples: u32) {}
+
+    fn set_current_voice_capacity(&self, _capacity: u32) {}
+}
+
+/// Sets the smoothers of all parameters to the current value of their parameter, which a host wrapper normally does
+/// when the plugin is initialized.
+///
+/// # Arguments
+///
+/// * `params`: The parameters of the plugin.
+fn reset_smoothers(params: &dyn Params) {
+    for (_, param, _) in params.param_map() {
+        // SAFETY: The pointers point into `params`, which outlives this loop.
+        unsafe {
+            match param {
+                ParamPtr::FloatParam(p) => (*p).smoothed.reset((*p).modulated_plain_value()),
+                ParamPtr::IntParam(p) => (*p).smoothed.reset((*p).modulated_plain_value()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Returns the number of channels of an optional channel count in an [`AudioIOLayout`].
+pub(crate) fn channel_count(channels: Option<NonZeroU32>) -> usize {
+    channels
+        .map(|channels| channels.get() as usize)
+        .unwrap_or(0)
+}
+
+/// Allocates silent channels for every auxiliary port of a layout.
+///
+/// # Arguments
+///
+/// * `ports`: The channel count of every port.
+/// * `buffer_size`: The number of samples in every channel.
+///
+/// # Returns
+///
+/// * `Vec<Vec<Vec<f32>>>`: The channels of every port.
+fn aux_storage(ports: &[NonZeroU32], buffer_size: usize) -> Vec<Vec<Vec<f32>>> {
+    ports
+        .iter()
+        .map(|channels| vec![vec![0.0; buffer_size]; channels.get() as usize])
+        .collect()
+}
+
+/// Creates a nih-plug [`Buffer`] that points into every channel of an auxiliary port.
+///
+/// # Arguments
+///
+/// * `channels`: The channels that the buffer points into, which all have the same length.
+///
+/// # Returns
+///
+/// * `Buffer`: The buffer, which borrows `channels` for as long as it's alive.
+fn buffer_of(channels: &mut [Vec<f32>]) -> Buffer<'_> {
+    let mut buffer = Buffer::default();
+    let samples = channels.first().map_or(0, Vec::len);
+
+    // SAFETY: Every slice has exactly `samples` samples and stays borrowed as long as the buffer.
+    unsafe {
+        buffer.set_slices(samples, |slices| {
+            slices.clear();
+            slices.extend(channels.iter_mut().map(Vec::as_mut_slice));
+        });
+    }
+
+    buffer
+}
+
+/// Shortens every channel of a buffer that was created by [`buffer_of`], for the last buffer of a file, without
+/// allocating.
+///
+/// # Arguments
+///
+/// * `buffer`: The buffer.
+/// * `samples`: The number of samples in every channel, which is at most the current number.
+fn shrink(buffer: &mut Buffer, samples: usize) {
+    if buffer.samples() == samples {
+        return;
+    }
+
+    // SAFETY: Every slice is shortened to `samples` samples and still points into the same channel.
+    unsafe {
+        buffer.set_slices(samples, |slices| {
+            for slice in slices.iter_mut() {
+                let channel = std::mem::take(slice);
+                *slice = &mut channel[..samples];
+            }
+        });
+    }
+}
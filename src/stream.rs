@@ -0,0 +1,433 @@
+use std::{
+    fs::File,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{
+    binary::BinaryWriter,
+    panic::{self, EmergencyFlush},
+    ring::{frame_ring, FrameConsumer, FrameProducer},
+    value::format_value,
+    ColumnType, CsvDialect, LladError, Metadata, OutputFormat, Value,
+};
+
+/// How long the writer thread sleeps when it has drained the ring buffer.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Streams frames from the audio thread to a file on a background thread. The audio thread fills a frame one
+/// column at a time, and every ended frame is handed to the writer thread through a lock-free ring buffer, so
+/// neither writing a value nor ending a frame allocates, locks or does I/O. Checking that every column has a value in
+/// a frame is left to the [`crate::SampleLogger`].
+///
+/// # Fields
+///
+/// * `producer`: The audio thread side of the ring buffer.
+/// * `frame`: The frame that is currently being filled, the bits of one value per column in registration order.
+/// * `history`: A ring buffer of frames that are held back until a trigger fires, laid out frame after frame.
+/// * `history_len`: The number of frames in `history`.
+/// * `history_next`: The index of the frame in `history` that is overwritten next.
+/// * `first_sample`: The index of the frame of the first row, shared with the writer thread for the index column of a
+///   CSV.
+/// * `writer`: The writer thread, `None` once it has been joined.
+/// * `flush`: Shared with the panic hook and the writer thread, tells whether the writer stopped early.
+pub(crate) struct Stream {
+    producer: FrameProducer,
+    frame: Vec<u64>,
+    history: Vec<u64>,
+    history_len: usize,
+    history_next: usize,
+    first_sample: Arc<AtomicU64>,
+    writer: Option<JoinHandle<Result<(), LladError>>>,
+    flush: Arc<EmergencyFlush>,
+}
+
+impl Stream {
+    /// Creates the output file, writes the header and starts the writer thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_file`: The name of the file where the frames will be written to.
+    /// * `format`: The format of the file.
+    /// * `dialect`: The dialect of the file if it is a CSV.
+    /// * `columns`: The key and type of every column, in the order in which they should be written.
+    /// * `order`: The indices in registration order of the columns in the order in which they should be written.
+    /// * `metadata`: The metadata known so far.
+    /// * `capacity`: The number of frames the ring buffer can hold before frames are dropped.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Stream, LladError>`: The running stream, or the error that occurred while creating the file,
+    ///   writing the header or spawning the thread.
+    pub fn start(
+        output_file: &str,
+        format: OutputFormat,
+        dialect: &CsvDialect,
+        columns: &[(&str, ColumnType)],
+        order: Vec<usize>,
+        metadata: &Metadata,
+        capacity: usize,
+    ) -> Result<Self, LladError> {
+        let first_sample = Arc::new(AtomicU64::new(metadata.first_sample));
+        let writer = match format {
+            OutputFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .delimiter(dialect.delimiter)
+                    .from_writer(File::create(output_file)?);
+                if dialect.header {
+                    let index = dialect.index.header(metadata.sample_rate);
+                    writer.write_record(
+                        index.into_iter().chain(columns.iter().map(|(key, _)| *key)),
+                    )?;
+                }
+
+                Sink::Csv(Box::new(CsvSink {
+                    writer,
+                    dialect: *dialect,
+                    sample_rate: metadata.sample_rate,
+                    first_sample: first_sample.clone(),
+                    rows: 0,
+                }))
+            }
+            OutputFormat::Binary => {
+                Sink::Binary(BinaryWriter::create(output_file, columns, metadata)?)
+            }
+        };
+        let types: Vec<ColumnType> = columns
+            .iter()
+            .map(|(_, column_type)| column_type.clone())
+            .collect();
+
+        let (producer, consumer) = frame_ring(order.len(), capacity);
+        let frame_size = order.len();
+        let flush = EmergencyFlush::register();
+        let output_file = String::from(output_file);
+        let handle = thread::Builder::new()
+            .name(String::from("llad-writer"))
+            .spawn({
+                let flush = Arc::clone(&flush);
+                move || drain(consumer, writer, types, order, flush, output_file)
+            })?;
+
+        Ok(Self {
+            producer,
+            frame: vec![0; frame_size],
+            history: Vec::new(),
+            history_len: 0,
+            history_next: 0,
+            first_sample,
+            writer: Some(handle),
+            flush,
+        })
+    }
+
+    /// Sets the value of a column in the current frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index of the column, in registration order.
+    /// * `value`: The value of the column in the current frame, which must already be of the type of the column.
+    ///
+    /// # Returns
+    ///
+    /// * `bool`: `true` if the value is stored, `false` if the column was registered after streaming started.
+    pub fn write(&mut self, index: usize, value: Value) -> bool {
+        match self.frame.get_mut(index) {
+            Some(slot) => {
+                *slot = value.to_bits();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Hands the current frame to the writer thread.
+    ///
+    /// # Returns
+    ///
+    /// * `bool`: `true` if the frame was handed over, `false` if the ring buffer is full and the frame was dropped.
+    pub fn end_frame(&mut self) -> bool {
+        self.producer.push(&self.frame)
+    }
+
+    /// Allocates the ring buffer of frames that are held back until a trigger fires.
+    ///
+    /// # Arguments
+    ///
+    /// * `frames`: The number of frames the ring buffer holds.
+    pub fn set_history(&mut self, frames: usize) {
+        self.history = vec![0; frames * self.frame.len()];
+        self.history_len = 0;
+        self.history_next = 0;
+    }
+
+    /// Keeps the current frame in the history instead of handing it to the writer thread, overwriting the oldest frame
+    /// in the history if it is full.
+    pub fn keep_frame(&mut self) {
+        let frames = self.history_frames();
+        if frames == 0 {
+            return;
+        }
+
+        let start = self.history_next * self.frame.len();
+        self.history[start..start + self.frame.len()].copy_from_slice(&self.frame);
+        self.history_next = (self.history_next + 1) % frames;
+        self.history_len = (self.history_len + 1).min(frames);
+    }
+
+    /// Hands all frames in the history to the writer thread, oldest first, and empties the history.
+    ///
+    /// # Arguments
+    ///
+    /// * `trigger_sample`: The index of the frame in which the trigger fired, which follows the frames in the history.
+    ///
+    /// # Returns
+    ///
+    /// * `bool`: `true` if all frames were handed over, `false` if the ring buffer is full and frames were dropped.
+    pub fn flush_history(&mut self, trigger_sample: u64) -> bool {
+        // Stored before the frames are pushed, so the writer thread sees it before it writes the first row.
+        self.first_sample.store(
+            trigger_sample.saturating_sub(self.history_len as u64),
+            Ordering::Release,
+        );
+
+        let frames = self.history_frames();
+        let oldest = if self.history_len == frames {
+            self.history_next
+        } else {
+            0
+        };
+
+        let mut handed_over = true;
+        for i in 0..self.history_len {
+            let start = (oldest + i) % frames * self.frame.len();
+            handed_over &= self
+                .producer
+                .push(&self.history[start..start + self.frame.len()]);
+        }
+
+        self.history_len = 0;
+        self.history_next = 0;
+        handed_over
+    }
+
+    /// Returns the number of frames the history can hold.
+    fn history_frames(&self) -> usize {
+        match self.frame.len() {
+            0 => 0,
+            frame_size => self.history.len() / frame_size,
+        }
+    }
+
+    /// Stops the writer thread after it has written all complete frames, and waits for it. A frame that is only
+    /// partially filled is not written.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if all frames were written, or the error of the writer thread.
+    pub fn finish(&mut self) -> Result<(), LladError> {
+        self.producer.close();
+
+        match self.writer.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(LladError::WriterPanicked),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `true` if the writer thread stopped early because of a panic, see [`crate::Metadata::truncated`], so
+    /// that the frames after it are not in the file.
+    pub fn truncated(&self) -> bool {
+        self.flush.done.load(Ordering::Acquire)
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        // Errors can't be reported from here, call `finish` to receive them.
+        let _ = self.finish();
+    }
+}
+
+/// The file that the writer thread writes the frames to.
+enum Sink {
+    Csv(Box<CsvSink>),
+    Binary(BinaryWriter),
+}
+
+/// A CSV that the writer thread writes the frames to.
+///
+/// # Fields
+///
+/// * `writer`: The CSV writer, of which the header has already been written.
+/// * `dialect`: The dialect of the CSV.
+/// * `sample_rate`: The sample rate, for an index column with the time of every row.
+/// * `first_sample`: The index of the frame of the first row, set by the audio thread when a trigger fires.
+/// * `rows`: The number of rows written so far.
+struct CsvSink {
+    writer: csv::Writer<File>,
+    dialect: CsvDialect,
+    sample_rate: Option<f32>,
+    first_sample: Arc<AtomicU64>,
+    rows: u64,
+}
+
+impl Sink {
+    /// Writes a frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`: The bits of the value of every column, in registration order.
+    /// * `types`: The type of every column, in the order in which they should be written.
+    /// * `order`: The indices in registration order of the columns in the order in which they should be written.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the frame is written, or the error while writing it.
+    fn write_frame(
+        &mut self,
+        frame: &[u64],
+        types: &[ColumnType],
+        order: &[usize],
+    ) -> Result<(), LladError> {
+        let values = order
+            .iter()
+            .zip(types)
+            .map(|(&i, column_type)| (column_type, Value::from_bits(column_type, frame[i])));
+
+        match self {
+            Sink::Csv(sink) => {
+                let sample = sink.first_sample.load(Ordering::Acquire) + sink.rows;
+                let floats = sink.dialect.floats;
+                let index = sink.dialect.index.field(sample, sink.sample_rate, floats);
+                let fields = values
+                    .map(|(column_type, value)| format_value(column_type.labels(), value, floats));
+
+                sink.writer.write_record(index.into_iter().chain(fields))?;
+                sink.rows += 1;
+            }
+            Sink::Binary(writer) => {
+                for (_, value) in values {
+                    writer.write_value(value)?;
+                }
+                writer.end_frame();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes all buffered frames to the file.
+    fn flush(&mut self) -> Result<(), LladError> {
+        match self {
+            Sink::Csv(sink) => sink.writer.flush()?,
+            Sink::Binary(writer) => writer.flush()?,
+        }
+
+        Ok(())
+    }
+}
+
+/// The body of the writer thread, writes every frame from the ring buffer to the file until the ring buffer is closed
+/// and drained. When the panic hook asks for it, writes the frames it has received so far, marks the file as truncated
+/// and stops. A binary file is left without the number of frames, which marks it as truncated, see
+/// [`OutputFormat::Binary`].
+///
+/// # Arguments
+///
+/// * `consumer`: The writer thread side of the ring buffer.
+/// * `writer`: The file, of which the header has already been written.
+/// * `types`: The type of every column, in the order in which they should be written.
+/// * `order`: The indices in registration order of the columns in the order in which they should be written.
+/// * `flush`: Shared with the panic hook, see [`crate::SampleLogger::install_panic_hook`].
+/// * `output_file`: The name of the file, to mark a CSV as truncated.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` once all frames are written, or the first error while writing.
+fn drain(
+    consumer: FrameConsumer,
+    mut writer: Sink,
+    types: Vec<ColumnType>,
+    order: Vec<usize>,
+    flush: Arc<EmergencyFlush>,
+    output_file: String,
+) -> Result<(), LladError> {
+    let mut frame = vec![0; types.len()];
+
+    loop {
+        // Checked before draining, so that every frame pushed before closing is written.
+        let closed = consumer.is_closed();
+        let panicked = flush.requested.load(Ordering::Acquire);
+
+        while consumer.pop(&mut frame) {
+            writer.write_frame(&frame, &types, &order)?;
+        }
+
+        if panicked {
+            writer.flush()?;
+            let marked = match writer {
+                Sink::Csv(_) => panic::mark_truncated(&output_file),
+                Sink::Binary(_) => Ok(()),
+            };
+            flush.done.store(true, Ordering::Release);
+            return marked;
+        }
+
+        if closed {
+            break;
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{read_csv_as_audio_data, LladError, SampleLogger};
+
+    #[test]
+    fn streamed_frames_are_written_in_order() {
+        let filename = std::env::temp_dir().join("llad_stream.csv");
+        let filename = filename.to_string_lossy().into_owned();
+
+        let mut logger = SampleLogger::new(filename.clone());
+        let column = logger.register("x");
+        logger.start_streaming(1024).unwrap();
+        for i in 0..100 {
+            logger.write_column(column, i as f32).unwrap();
+            logger.end_frame().unwrap();
+        }
+        logger.write_debug_values().unwrap();
+
+        let data = read_csv_as_audio_data(filename).unwrap();
+        let expected: Vec<f32> = (0..100).map(|i| i as f32).collect();
+        assert_eq!(data.get("x").unwrap().as_f32(), Some(&expected[..]));
+    }
+
+    #[test]
+    fn zero_capacity_drops_every_frame() {
+        let filename = std::env::temp_dir().join("llad_stream_empty.csv");
+        let filename = filename.to_string_lossy().into_owned();
+
+        let mut logger = SampleLogger::new(filename.clone());
+        let column = logger.register("x");
+        logger.start_streaming(0).unwrap();
+        logger.write_column(column, 1.0_f32).unwrap();
+        assert!(matches!(
+            logger.end_frame(),
+            Err(LladError::FrameDropped { sample: 0 })
+        ));
+        logger.write_debug_values().unwrap();
+
+        let data = read_csv_as_audio_data(filename).unwrap();
+        assert_eq!(data.get("x").map(|values| values.len()), Some(0));
+    }
+}
@@ -0,0 +1,263 @@
+use crate::{Column, Value};
+
+/// The direction in which a value has to cross the level of a [`Trigger::Threshold`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// Fires when the value goes from below the level to at or above it.
+    Rising,
+    /// Fires when the value goes from above the level to at or below it.
+    Falling,
+    /// Fires on both rising and falling crossings.
+    Both,
+}
+
+/// A condition that starts the capture of a [`crate::SampleLogger`], like the single-shot mode of an oscilloscope.
+/// See [`crate::SampleLogger::set_trigger`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    /// Fires when the value of `column` crosses `level` in the direction of `edge`. Values of any type are compared as
+    /// in [`Value::to_f64`], so e.g. a boolean column fires when it becomes `true` with a level of 0.5.
+    Threshold {
+        column: Column,
+        level: f32,
+        edge: Edge,
+    },
+    /// Fires when a NaN is written to `column`, or to any float column if `column` is `None`.
+    Nan { column: Option<Column> },
+    /// Only fires when [`crate::SampleLogger::trigger`] is called.
+    Manual,
+}
+
+/// The state of a triggered capture.
+///
+/// # Fields
+///
+/// * `trigger`: The condition that starts the capture.
+/// * `pre_trigger`: The number of frames before the trigger frame that are kept.
+/// * `post_trigger`: The number of frames, starting at the trigger frame, that are captured after the trigger.
+/// * `pending`: Whether the trigger condition has been met in the current frame.
+/// * `previous`: The last value of the column of a [`Trigger::Threshold`], to detect crossings.
+/// * `triggered_at`: The index of the frame in which the trigger fired.
+/// * `captured`: The number of frames captured since the trigger fired, including the trigger frame.
+pub(crate) struct Capture {
+    trigger: Trigger,
+    pub pre_trigger: usize,
+    post_trigger: usize,
+    pending: bool,
+    previous: Option<f64>,
+    pub triggered_at: Option<u64>,
+    captured: usize,
+}
+
+impl Capture {
+    /// Creates the state of a capture that has not been triggered yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `trigger`: The condition that starts the capture.
+    /// * `pre_trigger`: The number of frames before the trigger frame that are kept.
+    /// * `post_trigger`: The number of frames, starting at the trigger frame, that are captured after the trigger.
+    ///
+    /// # Returns
+    ///
+    /// * `Capture`: The newly created, armed capture.
+    pub fn new(trigger: Trigger, pre_trigger: usize, post_trigger: usize) -> Self {
+        Self {
+            trigger,
+            pre_trigger,
+            post_trigger: post_trigger.max(1),
+            pending: false,
+            previous: None,
+            triggered_at: None,
+            captured: 0,
+        }
+    }
+
+    /// Returns the number of frames a column holds at most during this capture.
+    pub fn frames(&self) -> usize {
+        self.pre_trigger + self.post_trigger
+    }
+
+    /// Checks a written value against the trigger condition.
+    ///
+    /// # Arguments
+    ///
+    /// * `column`: The column the value was written to.
+    /// * `value`: The written value.
+    pub fn observe(&mut self, column: Column, value: Value) {
+        if self.triggered_at.is_some() {
+            return;
+        }
+
+        match self.trigger {
+            Trigger::Threshold {
+                column: trigger_column,
+                level,
+                edge,
+            } if trigger_column == column => {
+                let value = value.to_f64();
+                let level = level as f64;
+                if let Some(previous) = self.previous {
+                    let rising = previous < level && value >= level;
+                    let falling = previous > level && value <= level;
+
+                    self.pending |= match edge {
+                        Edge::Rising => rising,
+                        Edge::Falling => falling,
+                        Edge::Both => rising || falling,
+                    };
+                }
+                self.previous = Some(value);
+            }
+            Trigger::Nan {
+                column: trigger_column,
+            } if trigger_column.is_none_or(|c| c == column) => {
+                self.pending |= value.to_f64().is_nan();
+            }
+            _ => {}
+        }
+    }
+
+    /// Makes the trigger fire at the end of the current frame, regardless of the trigger condition.
+    pub fn fire(&mut self) {
+        if self.triggered_at.is_none() {
+            self.pending = true;
+        }
+    }
+
+    /// Advances the capture by a frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample`: The index of the frame that is ended.
+    ///
+    /// # Returns
+    ///
+    /// * `FrameEnd`: What should happen with the ended frame.
+    pub fn end_frame(&mut self, sample: u64) -> FrameEnd {
+        if self.triggered_at.is_some() {
+            self.captured += 1;
+            FrameEnd::Capture
+        } else if self.pending {
+            self.triggered_at = Some(sample);
+            self.captured = 1;
+            FrameEnd::Trigger
+        } else {
+            FrameEnd::History
+        }
+    }
+
+    /// Returns `true` once all frames after the trigger have been captured.
+    pub fn is_done(&self) -> bool {
+        self.triggered_at.is_some() && self.captured >= self.post_trigger
+    }
+}
+
+/// What should happen with a frame that has been ended during a triggered capture.
+pub(crate) enum FrameEnd {
+    /// The trigger has not fired yet, the frame is kept as pre-trigger history.
+    History,
+    /// The trigger fired in this frame, the history is kept followed by this frame.
+    Trigger,
+    /// The trigger fired earlier, the frame is captured.
+    Capture,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_csv_as_audio_data, AudioData, SampleLogger};
+
+    /// Logs `x = 0, 1, ..` for `frames` frames with a trigger armed on `x`, and returns what was captured. The logger
+    /// writes the capture to `filename` in the temporary directory when it is dropped.
+    fn capture(
+        filename: &str,
+        level: f32,
+        pre_trigger: usize,
+        post_trigger: usize,
+        frames: usize,
+    ) -> AudioData {
+        let filename = std::env::temp_dir().join(filename);
+        let mut logger = SampleLogger::new(filename.to_string_lossy().into_owned());
+        let column = logger.register("x");
+        let trigger = Trigger::Threshold {
+            column,
+            level,
+            edge: Edge::Rising,
+        };
+        logger
+            .set_trigger(trigger, pre_trigger, post_trigger)
+            .unwrap();
+
+        for i in 0..frames {
+            logger.write_column(column, i as f32).unwrap();
+            logger.end_frame().unwrap();
+        }
+
+        logger.captured().unwrap()
+    }
+
+    fn values(data: &AudioData) -> Vec<f32> {
+        data.get("x").unwrap().as_f32().unwrap().to_vec()
+    }
+
+    #[test]
+    fn captures_pre_and_post_trigger_windows() {
+        let data = capture("llad_trigger.csv", 10.0, 3, 4, 30);
+
+        assert_eq!(values(&data), [7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0]);
+        assert_eq!(data.metadata().trigger_sample, Some(10));
+        assert_eq!(data.metadata().first_sample, 7);
+    }
+
+    #[test]
+    fn pre_trigger_window_is_cut_off_at_the_first_frame() {
+        let data = capture("llad_trigger_early.csv", 2.0, 5, 2, 30);
+
+        assert_eq!(values(&data), [0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(data.metadata().trigger_sample, Some(2));
+        assert_eq!(data.metadata().first_sample, 0);
+    }
+
+    #[test]
+    fn post_trigger_window_is_cut_off_at_the_last_frame() {
+        let data = capture("llad_trigger_late.csv", 8.0, 2, 5, 10);
+
+        assert_eq!(values(&data), [6.0, 7.0, 8.0, 9.0]);
+        assert_eq!(data.metadata().trigger_sample, Some(8));
+    }
+
+    #[test]
+    fn trigger_that_never_fires_captures_nothing() {
+        let data = capture("llad_trigger_never.csv", 100.0, 3, 4, 30);
+
+        assert!(values(&data).is_empty());
+        assert_eq!(data.metadata().trigger_sample, None);
+    }
+
+    #[test]
+    fn streamed_capture_matches_the_capture_in_memory() {
+        let filename = std::env::temp_dir().join("llad_trigger_stream.csv");
+        let filename = filename.to_string_lossy().into_owned();
+
+        let mut logger = SampleLogger::new(filename.clone());
+        let column = logger.register("x");
+        let trigger = Trigger::Threshold {
+            column,
+            level: 10.0,
+            edge: Edge::Rising,
+        };
+        logger.set_trigger(trigger, 3, 4).unwrap();
+        logger.start_streaming(64).unwrap();
+        for i in 0..30 {
+            logger.write_column(column, i as f32).unwrap();
+            logger.end_frame().unwrap();
+        }
+        logger.write_debug_values().unwrap();
+
+        let data = read_csv_as_audio_data(filename).unwrap();
+        assert_eq!(values(&data), [7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0]);
+        assert_eq!(data.metadata().trigger_sample, Some(10));
+        assert_eq!(data.metadata().first_sample, 7);
+    }
+}
@@ -0,0 +1,452 @@
+use crate::dialect::{self, FloatFormat};
+
+/// The type of the values of a column. Columns are [`ColumnType::F32`] unless registered otherwise with
+/// [`crate::SampleLogger::register_typed`], or first written to with a value of another type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnType {
+    F32,
+    F64,
+    I64,
+    Bool,
+    /// A small enum, e.g. the state of a state machine. Values are indices into the labels, and are written to the
+    /// CSV as their label.
+    Enum(Vec<String>),
+}
+
+impl ColumnType {
+    /// Creates the type of an enum column.
+    ///
+    /// # Arguments
+    ///
+    /// * `labels`: The label of every variant, indexed by the value of [`Value::Enum`].
+    ///
+    /// # Returns
+    ///
+    /// * `ColumnType`: The [`ColumnType::Enum`] with these labels.
+    pub fn enumeration(labels: &[&str]) -> Self {
+        ColumnType::Enum(labels.iter().map(|&label| String::from(label)).collect())
+    }
+
+    /// Returns the name of this type, as written to the sidecar file of a CSV.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ColumnType::F32 => "f32",
+            ColumnType::F64 => "f64",
+            ColumnType::I64 => "i64",
+            ColumnType::Bool => "bool",
+            ColumnType::Enum(_) => "enum",
+        }
+    }
+
+    /// Parses the name of a type, as written to the sidecar file of a CSV.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the type.
+    /// * `labels`: The labels of the variants, only used for enums.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<ColumnType>`: The type, or `None` if the name is unknown.
+    pub(crate) fn from_name(name: &str, labels: Vec<String>) -> Option<Self> {
+        match name {
+            "f32" => Some(ColumnType::F32),
+            "f64" => Some(ColumnType::F64),
+            "i64" => Some(ColumnType::I64),
+            "bool" => Some(ColumnType::Bool),
+            "enum" => Some(ColumnType::Enum(labels)),
+            _ => None,
+        }
+    }
+
+    /// Returns the labels of an enum type, or no labels for the other types.
+    pub(crate) fn labels(&self) -> &[String] {
+        match self {
+            ColumnType::Enum(labels) => labels,
+            _ => &[],
+        }
+    }
+
+    /// Parses a field of the CSV as a value of this type. Floats are read in every [`FloatFormat`].
+    ///
+    /// # Arguments
+    ///
+    /// * `field`: The field in the CSV.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Value>`: The value, or `None` if the field is not a value of this type.
+    pub(crate) fn parse(&self, field: &str) -> Option<Value> {
+        match self {
+            ColumnType::F32 => dialect::parse_hex(field)
+                .map(|value| value as f32)
+                .or_else(|| field.parse().ok())
+                .map(Value::F32),
+            ColumnType::F64 => dialect::parse_hex(field)
+                .or_else(|| field.parse().ok())
+                .map(Value::F64),
+            ColumnType::I64 => field.parse().ok().map(Value::I64),
+            ColumnType::Bool => match field {
+                "true" | "1" => Some(Value::Bool(true)),
+                "false" | "0" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ColumnType::Enum(labels) => match labels.iter().position(|label| label == field) {
+                Some(index) => Some(Value::Enum(index as u32)),
+                None => field.parse().ok().map(Value::Enum),
+            },
+        }
+    }
+}
+
+/// A single value written to a column. Values convert into the type of the column they are written to if that is
+/// lossless enough to be unsurprising: any number can be written to a [`ColumnType::F64`] column, but
+/// [`ColumnType::F32`] columns only take `f32` values so that nothing is narrowed silently, and integer, boolean and
+/// enum columns only take values of their own type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    F32(f32),
+    F64(f64),
+    I64(i64),
+    Bool(bool),
+    /// The index of the variant in the labels of a [`ColumnType::Enum`].
+    Enum(u32),
+}
+
+impl Value {
+    /// Returns the type of a column that would be created for this value. An enum column created this way has no
+    /// labels, so its values are written as their index.
+    pub(crate) fn column_type(self) -> ColumnType {
+        match self {
+            Value::F32(_) => ColumnType::F32,
+            Value::F64(_) => ColumnType::F64,
+            Value::I64(_) => ColumnType::I64,
+            Value::Bool(_) => ColumnType::Bool,
+            Value::Enum(_) => ColumnType::Enum(Vec::new()),
+        }
+    }
+
+    /// Returns the value as a float, for any type. Booleans are 0 or 1, enums are their index. Useful for plotting
+    /// and for comparing against a threshold.
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Value::F32(value) => value as f64,
+            Value::F64(value) => value,
+            Value::I64(value) => value as f64,
+            Value::Bool(value) => value as u8 as f64,
+            Value::Enum(index) => index as f64,
+        }
+    }
+
+    /// Returns the value as a float if it is a number, the values that can be written to a float column.
+    fn as_number(self) -> Option<f64> {
+        match self {
+            Value::F32(value) => Some(value as f64),
+            Value::F64(value) => Some(value),
+            Value::I64(value) => Some(value as f64),
+            Value::Bool(_) | Value::Enum(_) => None,
+        }
+    }
+
+    /// Returns the bits of the value, to pass it through the lock-free ring buffer to the writer thread.
+    pub(crate) fn to_bits(self) -> u64 {
+        match self {
+            Value::F32(value) => value.to_bits() as u64,
+            Value::F64(value) => value.to_bits(),
+            Value::I64(value) => value as u64,
+            Value::Bool(value) => value as u64,
+            Value::Enum(index) => index as u64,
+        }
+    }
+
+    /// Reconstructs a value from its bits, see [`Value::to_bits`].
+    ///
+    /// # Arguments
+    ///
+    /// * `column_type`: The type of the column the value was written to.
+    /// * `bits`: The bits of the value.
+    ///
+    /// # Returns
+    ///
+    /// * `Value`: The value of the type of the column.
+    pub(crate) fn from_bits(column_type: &ColumnType, bits: u64) -> Self {
+        match column_type {
+            ColumnType::F32 => Value::F32(f32::from_bits(bits as u32)),
+            ColumnType::F64 => Value::F64(f64::from_bits(bits)),
+            ColumnType::I64 => Value::I64(bits as i64),
+            ColumnType::Bool => Value::Bool(bits != 0),
+            ColumnType::Enum(_) => Value::Enum(bits as u32),
+        }
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::F32(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::F64(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::I64(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::I64(value as i64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+/// The values of a single column, stored in their own type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnData {
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    I64(Vec<i64>),
+    Bool(Vec<bool>),
+    /// The indices of the variants, and the label of every variant.
+    Enum {
+        labels: Vec<String>,
+        values: Vec<u32>,
+    },
+}
+
+impl ColumnData {
+    /// Creates an empty column that has space for `capacity` values without reallocating.
+    ///
+    /// # Arguments
+    ///
+    /// * `column_type`: The type of the values of the column.
+    /// * `capacity`: The number of values to reserve space for.
+    ///
+    /// # Returns
+    ///
+    /// * `ColumnData`: The newly created, empty column.
+    pub fn with_capacity(column_type: ColumnType, capacity: usize) -> Self {
+        match column_type {
+            ColumnType::F32 => ColumnData::F32(Vec::with_capacity(capacity)),
+            ColumnType::F64 => ColumnData::F64(Vec::with_capacity(capacity)),
+            ColumnType::I64 => ColumnData::I64(Vec::with_capacity(capacity)),
+            ColumnType::Bool => ColumnData::Bool(Vec::with_capacity(capacity)),
+            ColumnType::Enum(labels) => ColumnData::Enum {
+                labels,
+                values: Vec::with_capacity(capacity),
+            },
+        }
+    }
+
+    /// Returns the type of the values of this column.
+    pub fn column_type(&self) -> ColumnType {
+        match self {
+            ColumnData::F32(_) => ColumnType::F32,
+            ColumnData::F64(_) => ColumnType::F64,
+            ColumnData::I64(_) => ColumnType::I64,
+            ColumnData::Bool(_) => ColumnType::Bool,
+            ColumnData::Enum { labels, .. } => ColumnType::Enum(labels.clone()),
+        }
+    }
+
+    /// Returns the number of values in this column.
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnData::F32(values) => values.len(),
+            ColumnData::F64(values) => values.len(),
+            ColumnData::I64(values) => values.len(),
+            ColumnData::Bool(values) => values.len(),
+            ColumnData::Enum { values, .. } => values.len(),
+        }
+    }
+
+    /// Returns `true` if this column has no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a single value of this column.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index of the value.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Value>`: The value, or `None` if the column is shorter.
+    pub fn get(&self, index: usize) -> Option<Value> {
+        match self {
+            ColumnData::F32(values) => values.get(index).copied().map(Value::F32),
+            ColumnData::F64(values) => values.get(index).copied().map(Value::F64),
+            ColumnData::I64(values) => values.get(index).copied().map(Value::I64),
+            ColumnData::Bool(values) => values.get(index).copied().map(Value::Bool),
+            ColumnData::Enum { values, .. } => values.get(index).copied().map(Value::Enum),
+        }
+    }
+
+    /// Returns the values of an [`ColumnType::F32`] column.
+    pub fn as_f32(&self) -> Option<&[f32]> {
+        match self {
+            ColumnData::F32(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of an [`ColumnType::F64`] column.
+    pub fn as_f64(&self) -> Option<&[f64]> {
+        match self {
+            ColumnData::F64(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of an [`ColumnType::I64`] column.
+    pub fn as_i64(&self) -> Option<&[i64]> {
+        match self {
+            ColumnData::I64(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of a [`ColumnType::Bool`] column.
+    pub fn as_bool(&self) -> Option<&[bool]> {
+        match self {
+            ColumnData::Bool(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the indices and labels of a [`ColumnType::Enum`] column.
+    pub fn as_enum(&self) -> Option<(&[u32], &[String])> {
+        match self {
+            ColumnData::Enum { labels, values } => Some((values, labels)),
+            _ => None,
+        }
+    }
+
+    /// Converts all values of this column to floats, see [`Value::to_f64`]. Useful for plotting columns of any type.
+    pub fn to_f64(&self) -> Vec<f64> {
+        (0..self.len())
+            .filter_map(|i| self.get(i))
+            .map(Value::to_f64)
+            .collect()
+    }
+
+    /// Converts a value into the type of this column, see [`Value`] for which conversions are allowed.
+    ///
+    /// # Arguments
+    ///
+    /// * `value`: The value to convert.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Value>`: The value in the type of this column, or `None` if it can't be written to this column.
+    pub(crate) fn cast(&self, value: Value) -> Option<Value> {
+        match (self, value) {
+            (ColumnData::F64(_), value) => value.as_number().map(Value::F64),
+            (ColumnData::F32(_), Value::F32(_))
+            | (ColumnData::I64(_), Value::I64(_))
+            | (ColumnData::Bool(_), Value::Bool(_))
+            | (ColumnData::Enum { .. }, Value::Enum(_)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Appends a value, which must already be of the type of this column. Values of other types are ignored.
+    pub(crate) fn push(&mut self, value: Value) {
+        match (self, value) {
+            (ColumnData::F32(values), Value::F32(value)) => values.push(value),
+            (ColumnData::F64(values), Value::F64(value)) => values.push(value),
+            (ColumnData::I64(values), Value::I64(value)) => values.push(value),
+            (ColumnData::Bool(values), Value::Bool(value)) => values.push(value),
+            (ColumnData::Enum { values, .. }, Value::Enum(value)) => values.push(value),
+            _ => {}
+        }
+    }
+
+    /// Overwrites a value, which must already be of the type of this column. Values of other types are ignored.
+    pub(crate) fn set(&mut self, index: usize, value: Value) {
+        match (self, value) {
+            (ColumnData::F32(values), Value::F32(value)) => values[index] = value,
+            (ColumnData::F64(values), Value::F64(value)) => values[index] = value,
+            (ColumnData::I64(values), Value::I64(value)) => values[index] = value,
+            (ColumnData::Bool(values), Value::Bool(value)) => values[index] = value,
+            (ColumnData::Enum { values, .. }, Value::Enum(value)) => values[index] = value,
+            _ => {}
+        }
+    }
+
+    /// Formats a single value for the CSV, see [`format_value`].
+    pub(crate) fn format(&self, index: usize, floats: FloatFormat) -> Option<String> {
+        let labels = self.as_enum().map_or(&[][..], |(_, labels)| labels);
+        self.get(index)
+            .map(|value| format_value(labels, value, floats))
+    }
+
+    /// Returns the number of values this column can hold without reallocating.
+    pub(crate) fn capacity(&self) -> usize {
+        match self {
+            ColumnData::F32(values) => values.capacity(),
+            ColumnData::F64(values) => values.capacity(),
+            ColumnData::I64(values) => values.capacity(),
+            ColumnData::Bool(values) => values.capacity(),
+            ColumnData::Enum { values, .. } => values.capacity(),
+        }
+    }
+
+    /// Reserves space for at least `additional` more values.
+    pub(crate) fn reserve_exact(&mut self, additional: usize) {
+        match self {
+            ColumnData::F32(values) => values.reserve_exact(additional),
+            ColumnData::F64(values) => values.reserve_exact(additional),
+            ColumnData::I64(values) => values.reserve_exact(additional),
+            ColumnData::Bool(values) => values.reserve_exact(additional),
+            ColumnData::Enum { values, .. } => values.reserve_exact(additional),
+        }
+    }
+
+    /// Rotates the values in place so that the value at `mid` comes first.
+    pub(crate) fn rotate_left(&mut self, mid: usize) {
+        match self {
+            ColumnData::F32(values) => values.rotate_left(mid),
+            ColumnData::F64(values) => values.rotate_left(mid),
+            ColumnData::I64(values) => values.rotate_left(mid),
+            ColumnData::Bool(values) => values.rotate_left(mid),
+            ColumnData::Enum { values, .. } => values.rotate_left(mid),
+        }
+    }
+}
+
+/// Formats a value for the CSV.
+///
+/// # Arguments
+///
+/// * `labels`: The labels of the variants if the value is an enum.
+/// * `value`: The value to format.
+/// * `floats`: How float values are written.
+///
+/// # Returns
+///
+/// * `String`: The field of the value in the CSV. Enum values without a label are written as their index.
+pub(crate) fn format_value(labels: &[String], value: Value, floats: FloatFormat) -> String {
+    match value {
+        Value::F32(value) => floats.format_f32(value),
+        Value::F64(value) => floats.format_f64(value),
+        Value::I64(value) => value.to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Enum(index) => match labels.get(index as usize) {
+            Some(label) => label.clone(),
+            None => index.to_string(),
+        },
+    }
+}
@@ -0,0 +1,637 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    sync::Arc,
+};
+
+use crate::{AudioData, ColumnData, ColumnType, LladError, SampleLogger, Value};
+
+/// The name of the outermost scope of a VCD written by [`write_vcd`], which [`read_vcd`] leaves out of the keys again.
+const TOP_SCOPE: &str = "llad";
+
+/// The number of ticks of the VCD timescale in a second, the timescale is 1 ns.
+const TICKS_PER_SECOND: f64 = 1e9;
+
+impl SampleLogger {
+    /// Writes the logged data as a Value Change Dump, see [`write_vcd`], so it can be inspected in a waveform viewer
+    /// such as GTKWave or Surfer. Call this on shutdown of the plugin, next to [`SampleLogger::write_debug_values`].
+    ///
+    /// # Arguments
+    ///
+    /// * `filename`: The name of the VCD file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the file is written or if logging is disabled. Returns
+    ///   [`LladError::NotInMemory`] when streaming, or the error while writing the file.
+    pub fn write_vcd(&self, filename: String) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            write_vcd(filename, &self.captured()?)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes columns as a Value Change Dump (VCD), the waveform format of hardware simulators. Every column becomes a
+/// signal: float columns are `real` signals, `i64` columns 64 bit `integer` signals, `bool` columns 1 bit `wire`s and
+/// enum columns `integer` signals of their variant index. Dotted keys become nested scopes, so `filter.lp.state` is
+/// the signal `state` in the scope `lp` in the scope `filter`, all in the scope `llad`. The signals are declared scope
+/// by scope, in the order of the columns within a scope.
+///
+/// The timescale is 1 ns, and the time of every row is derived from the sample rate in [`AudioData::metadata`], so
+/// that viewers show real time. Without a sample rate, every row is 1 ns. The sample rate, the first frame and the
+/// labels of enums are recorded in `$comment`s, from which [`read_vcd`] restores them.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the VCD file.
+/// * `data`: The columns to write, e.g. as read by [`crate::read_csv_as_audio_data`].
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the file is written, or the error while writing it.
+pub fn write_vcd(filename: String, data: &AudioData) -> Result<(), LladError> {
+    let mut file = BufWriter::new(File::create(filename)?);
+    let metadata = data.metadata();
+    let ids: Vec<String> = (0..data.len()).map(identifier).collect();
+
+    writeln!(file, "$version LLAD $end")?;
+    writeln!(file, "$timescale 1 ns $end")?;
+    if let Some(sample_rate) = metadata.sample_rate {
+        writeln!(file, "$comment llad sample_rate {sample_rate} $end")?;
+    }
+    writeln!(
+        file,
+        "$comment llad first_sample {} $end",
+        metadata.first_sample
+    )?;
+    for ((_, values), id) in data.iter().zip(&ids) {
+        if let ColumnType::Enum(labels) = values.column_type() {
+            let labels: Vec<String> = labels.iter().map(|label| escape(label)).collect();
+            writeln!(file, "$comment llad enum {id} {} $end", labels.join(" "))?;
+        }
+    }
+
+    write_scopes(&mut file, data, &ids)?;
+    writeln!(file, "$enddefinitions $end")?;
+
+    let rows = data
+        .iter()
+        .map(|(_, values)| values.len())
+        .max()
+        .unwrap_or(0);
+    let time = |row: usize| {
+        let sample = metadata.first_sample + row as u64;
+        match metadata.sample_rate {
+            Some(sample_rate) => {
+                (sample as f64 * TICKS_PER_SECOND / f64::from(sample_rate)).round() as u64
+            }
+            None => sample,
+        }
+    };
+
+    let mut previous: Vec<Option<u64>> = vec![None; data.len()];
+    for row in 0..rows {
+        let mut timestamp_written = false;
+
+        for (i, (_, values)) in data.iter().enumerate() {
+            let Some(value) = values.get(row) else {
+                continue;
+            };
+            if previous[i] == Some(value.to_bits()) {
+                continue;
+            }
+            previous[i] = Some(value.to_bits());
+
+            if !timestamp_written {
+                writeln!(file, "#{}", time(row))?;
+                if row == 0 {
+                    writeln!(file, "$dumpvars")?;
+                }
+                timestamp_written = true;
+            }
+            writeln!(file, "{}", format_change(value, &ids[i]))?;
+        }
+
+        if row == 0 && timestamp_written {
+            writeln!(file, "$end")?;
+        }
+    }
+
+    // Marks the end of the last row, so that readers know how long its values last.
+    writeln!(file, "#{}", time(rows))?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Writes the `$scope` and `$var` declarations of all columns, nesting the scopes of dotted keys.
+///
+/// # Arguments
+///
+/// * `file`: The VCD file.
+/// * `data`: The columns.
+/// * `ids`: The identifier code of every column.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the declarations are written, or the error while writing them.
+fn write_scopes(file: &mut impl Write, data: &AudioData, ids: &[String]) -> Result<(), LladError> {
+    // Sorted by scope so that every scope is opened once, the columns keep their order within a scope.
+    let mut columns: Vec<(Vec<&str>, &str, &ColumnData, &str)> = data
+        .iter()
+        .zip(ids)
+        .map(|((key, values), id)| {
+            let mut path: Vec<&str> = key.split('.').collect();
+            let name = path.pop().unwrap_or_default();
+            (path, name, values, id.as_str())
+        })
+        .collect();
+    columns.sort_by(|a, b| a.0.cmp(&b.0));
+
+    writeln!(file, "$scope module {TOP_SCOPE} $end")?;
+    let mut open: Vec<&str> = Vec::new();
+
+    for (path, name, values, id) in columns {
+        let common = open
+            .iter()
+            .zip(&path)
+            .take_while(|(open, scope)| open == scope)
+            .count();
+        for _ in common..open.len() {
+            writeln!(file, "$upscope $end")?;
+        }
+        open.truncate(common);
+        for &scope in &path[common..] {
+            writeln!(file, "$scope module {scope} $end")?;
+            open.push(scope);
+        }
+
+        let (kind, size) = match values.column_type() {
+            ColumnType::F32 => ("real", 32),
+            ColumnType::F64 => ("real", 64),
+            ColumnType::I64 => ("integer", 64),
+            ColumnType::Bool => ("wire", 1),
+            ColumnType::Enum(_) => ("integer", 32),
+        };
+        writeln!(file, "$var {kind} {size} {id} {name} $end")?;
+    }
+
+    for _ in 0..=open.len() {
+        writeln!(file, "$upscope $end")?;
+    }
+
+    Ok(())
+}
+
+/// Returns the identifier code of a signal, a short string of printable ASCII characters.
+///
+/// # Arguments
+///
+/// * `index`: The index of the column.
+///
+/// # Returns
+///
+/// * `String`: The identifier code of the column.
+fn identifier(mut index: usize) -> String {
+    let mut id = String::new();
+
+    loop {
+        id.push(char::from(b'!' + (index % 94) as u8));
+        index /= 94;
+        if index == 0 {
+            return id;
+        }
+        index -= 1;
+    }
+}
+
+/// Formats a value change of a signal.
+///
+/// # Arguments
+///
+/// * `value`: The new value of the signal.
+/// * `id`: The identifier code of the signal.
+///
+/// # Returns
+///
+/// * `String`: The value change, e.g. `r0.5 !` or `1"`.
+fn format_change(value: Value, id: &str) -> String {
+    match value {
+        Value::F32(value) => format!("r{value} {id}"),
+        Value::F64(value) => format!("r{value} {id}"),
+        Value::I64(value) => format!("b{:b} {id}", value as u64),
+        Value::Bool(value) => format!("{}{id}", u8::from(value)),
+        Value::Enum(value) => format!("b{value:b} {id}"),
+    }
+}
+
+/// Escapes whitespace and `%` in an enum label, so that the label is a single token in a `$comment`.
+fn escape(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| match c {
+            '%' => String::from("%25"),
+            c if c.is_whitespace() => format!("%{:02X}", u32::from(c)),
+            c => String::from(c),
+        })
+        .collect()
+}
+
+/// Undoes [`escape`].
+fn unescape(label: &str) -> String {
+    let mut unescaped = String::new();
+    let mut rest = label;
+
+    while let Some(index) = rest.find('%') {
+        unescaped.push_str(&rest[..index]);
+        let code = rest
+            .get(index + 1..index + 3)
+            .and_then(|code| u32::from_str_radix(code, 16).ok())
+            .and_then(char::from_u32);
+        match code {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[index + 3..];
+            }
+            None => {
+                unescaped.push('%');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// A signal declared in a VCD.
+///
+/// # Fields
+///
+/// * `key`: The key of its column, the names of its scopes and itself joined by dots.
+/// * `id`: Its identifier code.
+/// * `column_type`: The type of its column.
+struct Signal {
+    key: String,
+    id: String,
+    column_type: ColumnType,
+}
+
+/// Reads a Value Change Dump into columns, with a row for every sample. This reads the files written by [`write_vcd`]
+/// back into the columns, types and metadata they were written from, and also reads VCDs of other tools: `real`
+/// signals become [`ColumnType::F64`] columns, 1 bit signals [`ColumnType::Bool`] columns and wider signals
+/// [`ColumnType::I64`] columns, where `x` and `z` bits are read as 0. The outermost scope, usually the top module or
+/// testbench, is left out of the keys. Without the sample rate that [`write_vcd`] records, the shortest time between
+/// two value changes is taken as the length of a row.
+///
+/// # Arguments
+///
+/// * `filename` - A string representing the path to the VCD file.
+///
+/// # Returns
+///
+/// * `Result<AudioData, LladError>` - On success, returns the columns in the order in which their signals are declared.
+///   On failure, returns an error.
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// * The file cannot be read, as [`LladError::Io`].
+/// * A value change can't be parsed, as [`LladError::Parse`] with the identifier code of the signal and the index of
+///   the time.
+pub fn read_vcd(filename: String) -> Result<AudioData, LladError> {
+    let contents = fs::read_to_string(filename)?;
+    let mut tokens = contents.split_whitespace();
+
+    let mut signals: Vec<Signal> = Vec::new();
+    let mut scopes: Vec<String> = Vec::new();
+    let mut labels: HashMap<String, Vec<String>> = HashMap::new();
+    let mut sample_rate: Option<f32> = None;
+    let mut first_sample: Option<u64> = None;
+    let mut seconds_per_tick = 1e-9;
+
+    while let Some(token) = tokens.next() {
+        let section: Vec<&str> = tokens
+            .by_ref()
+            .take_while(|&token| token != "$end")
+            .collect();
+
+        match (token, section.as_slice()) {
+            ("$enddefinitions", _) => break,
+            ("$timescale", section) => seconds_per_tick = parse_timescale(&section.concat()),
+            ("$scope", [_, name, ..]) => scopes.push(String::from(*name)),
+            ("$upscope", _) => {
+                scopes.pop();
+            }
+            ("$var", [kind, size, id, name, rest @ ..]) => {
+                // The outermost scope is the name of the design, which is left out of the key.
+                let mut path: Vec<&str> = scopes.iter().skip(1).map(String::as_str).collect();
+                let name = format!("{name}{}", rest.concat());
+                path.push(&name);
+
+                signals.push(Signal {
+                    key: path.join("."),
+                    id: String::from(*id),
+                    column_type: match (*kind, *size) {
+                        ("real", "32") => ColumnType::F32,
+                        ("real" | "realtime", _) => ColumnType::F64,
+                        (_, "1") => ColumnType::Bool,
+                        _ => ColumnType::I64,
+                    },
+                });
+            }
+            ("$comment", ["llad", "sample_rate", value]) => sample_rate = value.parse().ok(),
+            ("$comment", ["llad", "first_sample", value]) => first_sample = value.parse().ok(),
+            ("$comment", ["llad", "enum", id, enum_labels @ ..]) => {
+                labels.insert(
+                    String::from(*id),
+                    enum_labels.iter().map(|label| unescape(label)).collect(),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    for signal in &mut signals {
+        if let Some(labels) = labels.remove(&signal.id) {
+            signal.column_type = ColumnType::Enum(labels);
+        }
+    }
+
+    let changes = read_changes(tokens, &signals)?;
+
+    let ticks_per_row = match sample_rate {
+        Some(sample_rate) => 1.0 / (f64::from(sample_rate) * seconds_per_tick),
+        None => changes
+            .windows(2)
+            .map(|pair| pair[1].0 - pair[0].0)
+            .filter(|&delta| delta > 0)
+            .min()
+            .unwrap_or(1) as f64,
+    };
+    let row_of = |time: u64| (time as f64 / ticks_per_row).round() as u64;
+    let first_row =
+        first_sample.unwrap_or_else(|| changes.first().map_or(0, |&(time, _)| row_of(time)));
+
+    let mut data = AudioData::new();
+    data.metadata.sample_rate = sample_rate;
+    data.metadata.first_sample = first_row;
+    for signal in &signals {
+        data.push_column(
+            signal.key.clone(),
+            ColumnData::with_capacity(signal.column_type.clone(), 0),
+        );
+    }
+
+    // Every value lasts until the next change of its signal, the last time marks the end of the last row.
+    let mut current: Vec<Value> = signals
+        .iter()
+        .map(|signal| Value::from_bits(&signal.column_type, 0))
+        .collect();
+    let mut rows = 0;
+    for (time, values) in changes {
+        let row = row_of(time).saturating_sub(first_row) as usize;
+        for _ in rows..row {
+            for (column, &value) in data.columns.iter_mut().zip(&current) {
+                column.push(value);
+            }
+        }
+        rows = rows.max(row);
+
+        for (index, value) in values {
+            current[index] = value;
+        }
+    }
+
+    Ok(data)
+}
+
+/// The value changes at a time, as the index of the signal and its new value.
+type Changes = Vec<(usize, Value)>;
+
+/// Reads the value changes after the definitions of a VCD.
+///
+/// # Arguments
+///
+/// * `tokens`: The tokens after `$enddefinitions $end`.
+/// * `signals`: The declared signals.
+///
+/// # Returns
+///
+/// * `Result<Vec<(u64, Changes)>, LladError>`: The value changes at every time, in order, followed by the last time
+///   without changes. Returns [`LladError::Parse`] if a value can't be parsed.
+fn read_changes<'a>(
+    mut tokens: impl Iterator<Item = &'a str>,
+    signals: &[Signal],
+) -> Result<Vec<(u64, Changes)>, LladError> {
+    let mut indices: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, signal) in signals.iter().enumerate() {
+        indices.entry(signal.id.as_str()).or_default().push(index);
+    }
+
+    let mut changes: Vec<(u64, Changes)> = Vec::new();
+    let parse_error = |id: &str, row: usize, value: &str| LladError::Parse {
+        column: Arc::from(id),
+        row,
+        value: String::from(value),
+    };
+
+    while let Some(token) = tokens.next() {
+        let (value, id) = match token.as_bytes().first() {
+            Some(b'#') => {
+                let time = token[1..]
+                    .parse()
+                    .map_err(|_| parse_error("time", changes.len(), token))?;
+                changes.push((time, Vec::new()));
+                continue;
+            }
+            Some(b'$') => continue,
+            Some(b'r' | b'R' | b'b' | b'B' | b's' | b'S') => {
+                (token, tokens.next().unwrap_or_default())
+            }
+            _ => token.split_at(1.min(token.len())),
+        };
+
+        let Some(indices) = indices.get(id) else {
+            continue;
+        };
+        let time = changes.len().saturating_sub(1);
+        let Some((_, values)) = changes.last_mut() else {
+            continue;
+        };
+
+        for &index in indices {
+            let parsed = parse_value(&signals[index].column_type, value)
+                .ok_or_else(|| parse_error(id, time, value))?;
+            values.push((index, parsed));
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Parses the value of a value change into the type of a column.
+///
+/// # Arguments
+///
+/// * `column_type`: The type of the column.
+/// * `value`: The value, including its `r` or `b` prefix for vectors.
+///
+/// # Returns
+///
+/// * `Option<Value>`: The value, or `None` if it can't be parsed.
+fn parse_value(column_type: &ColumnType, value: &str) -> Option<Value> {
+    let (prefix, digits) = value.split_at(1.min(value.len()));
+
+    match prefix {
+        "r" | "R" => {
+            let number: f64 = digits.parse().ok()?;
+            match column_type {
+                ColumnType::F32 => Some(Value::F32(digits.parse().ok()?)),
+                ColumnType::Bool => Some(Value::Bool(number != 0.0)),
+                ColumnType::I64 => Some(Value::I64(number as i64)),
+                ColumnType::Enum(_) => Some(Value::Enum(number as u32)),
+                ColumnType::F64 => Some(Value::F64(number)),
+            }
+        }
+        "s" | "S" => None,
+        _ => {
+            let bits = if matches!(prefix, "b" | "B") {
+                digits
+            } else {
+                value
+            };
+            // Unknown and high impedance bits are read as 0.
+            let bits: String = bits
+                .chars()
+                .map(|bit| if bit == '1' { '1' } else { '0' })
+                .collect();
+            let number = u64::from_str_radix(&bits, 2).ok()?;
+
+            Some(match column_type {
+                ColumnType::F32 => Value::F32(number as f32),
+                ColumnType::F64 => Value::F64(number as f64),
+                ColumnType::I64 => Value::I64(number as i64),
+                ColumnType::Bool => Value::Bool(number != 0),
+                ColumnType::Enum(_) => Value::Enum(number as u32),
+            })
+        }
+    }
+}
+
+/// Parses a `$timescale`, e.g. `1ns` or `10 us`.
+///
+/// # Arguments
+///
+/// * `timescale`: The timescale, without whitespace.
+///
+/// # Returns
+///
+/// * `f64`: The length of a tick in seconds, 1 ns if the timescale can't be parsed.
+fn parse_timescale(timescale: &str) -> f64 {
+    let split = timescale
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(timescale.len());
+    let (number, unit) = timescale.split_at(split);
+    let number: f64 = number.parse().unwrap_or(1.0);
+
+    let unit = match unit {
+        "s" => 1.0,
+        "ms" => 1e-3,
+        "us" => 1e-6,
+        "ns" => 1e-9,
+        "ps" => 1e-12,
+        "fs" => 1e-15,
+        _ => return 1e-9,
+    };
+
+    number * unit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> String {
+        std::env::temp_dir()
+            .join(name)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn written_columns_are_read_back() {
+        let filename = temp_file("llad_vcd.csv");
+        let mut logger = SampleLogger::new(filename);
+        logger.set_sample_rate(48000.0);
+        let state =
+            logger.register_typed("filter.lp.state", ColumnType::enumeration(&["idle", "run"]));
+        let input = logger.register("input");
+        let gain = logger.register_typed("filter.gain", ColumnType::F64);
+        let count = logger.register_typed("count", ColumnType::I64);
+        let gate = logger.register_typed("gate", ColumnType::Bool);
+
+        // Repeated values are only written once, and must still read back as a value in every row.
+        for i in 0..8 {
+            logger
+                .write_column(state, Value::Enum(u32::from(i >= 4)))
+                .unwrap();
+            logger
+                .write_column(input, [0.5_f32, -1.0, 1e-40][i % 3])
+                .unwrap();
+            logger
+                .write_column(gain, [0.1, 0.1, 5e-324, -1e300][i % 4])
+                .unwrap();
+            logger.write_column(count, i as i64 - 4).unwrap();
+            logger.write_column(gate, i % 3 == 0).unwrap();
+            logger.end_frame().unwrap();
+        }
+
+        let vcd = temp_file("llad.vcd");
+        logger.write_vcd(vcd.clone()).unwrap();
+        let data = read_vcd(vcd).unwrap();
+        let expected = logger.captured().unwrap();
+
+        assert_eq!(data.len(), expected.len());
+        for (key, values) in expected.iter() {
+            assert_eq!(data.get(key), Some(values), "{key}");
+        }
+        assert_eq!(data.metadata().sample_rate, Some(48000.0));
+        assert_eq!(data.metadata().first_sample, 0);
+    }
+
+    #[test]
+    fn vcds_of_other_tools_are_read() {
+        let vcd = temp_file("llad_other.vcd");
+        fs::write(
+            &vcd,
+            "$timescale 1 us $end\n\
+             $scope module top $end\n\
+             $var wire 1 ! clk $end\n\
+             $var wire 4 \" bus $end\n\
+             $var real 64 # level $end\n\
+             $upscope $end\n\
+             $enddefinitions $end\n\
+             #0\n0!\nb1x01 \"\nr0.5 #\n\
+             #10\n1!\n\
+             #20\n0!\nb11 \"\nr-2 #\n\
+             #30\n",
+        )
+        .unwrap();
+
+        let data = read_vcd(vcd).unwrap();
+        assert_eq!(
+            data.get("clk").unwrap().as_bool(),
+            Some(&[false, true, false][..])
+        );
+        assert_eq!(data.get("bus").unwrap().as_i64(), Some(&[9, 9, 3][..]));
+        assert_eq!(
+            data.get("level").unwrap().as_f64(),
+            Some(&[0.5, 0.5, -2.0][..])
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use crate::Value;
+
+/// What is wrong with a value caught by the watchdog, see [`crate::SampleLogger::set_watchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnomalyKind {
+    /// The value is NaN.
+    Nan,
+    /// The value is positive or negative infinity.
+    Infinite,
+    /// The value is subnormal (denormal), which is very slow to compute with on most CPUs.
+    Subnormal,
+}
+
+impl AnomalyKind {
+    /// Returns the name of the kind as written to the sidecar file.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            AnomalyKind::Nan => "nan",
+            AnomalyKind::Infinite => "inf",
+            AnomalyKind::Subnormal => "subnormal",
+        }
+    }
+
+    /// Returns the kind with the given name, see [`AnomalyKind::name`].
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "nan" => Some(AnomalyKind::Nan),
+            "inf" => Some(AnomalyKind::Infinite),
+            "subnormal" => Some(AnomalyKind::Subnormal),
+            _ => None,
+        }
+    }
+
+    /// Checks a value for an anomaly. Only float values can have one.
+    ///
+    /// # Arguments
+    ///
+    /// * `value`: The value to check.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<AnomalyKind>`: What is wrong with the value, or `None` if it is a normal number or zero.
+    pub(crate) fn of(value: Value) -> Option<Self> {
+        let (nan, infinite, subnormal) = match value {
+            Value::F32(value) => (value.is_nan(), value.is_infinite(), value.is_subnormal()),
+            Value::F64(value) => (value.is_nan(), value.is_infinite(), value.is_subnormal()),
+            _ => return None,
+        };
+
+        match (nan, infinite, subnormal) {
+            (true, _, _) => Some(AnomalyKind::Nan),
+            (_, true, _) => Some(AnomalyKind::Infinite),
+            (_, _, true) => Some(AnomalyKind::Subnormal),
+            _ => None,
+        }
+    }
+}
+
+/// The first anomaly in a column, as reported by [`crate::SampleLogger::anomalies`] and written to the sidecar file.
+///
+/// # Fields
+///
+/// * `column`: The key of the column.
+/// * `sample`: The index of the frame in which the anomaly first appeared, counted from the first frame the logger saw.
+/// * `kind`: What was wrong with the value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anomaly {
+    pub column: String,
+    pub sample: u64,
+    pub kind: AnomalyKind,
+}
+
+/// What the watchdog does when it catches an anomaly, see [`crate::SampleLogger::set_watchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogAction {
+    /// Only records the anomaly.
+    Record,
+    /// Records the anomaly and stops logging after the frame in which it appeared.
+    Stop,
+    /// Records the anomaly and fires the armed [`crate::Trigger`], if any, in the frame in which it appeared.
+    Trigger,
+}
+
+/// The state of the watchdog of a [`crate::SampleLogger`].
+///
+/// # Fields
+///
+/// * `action`: What to do when an anomaly is caught.
+/// * `first`: The kind and frame of the first anomaly of every column, indexed by [`crate::Column`].
+/// * `stop_after`: The frame after which logging stops, for [`WatchdogAction::Stop`].
+pub(crate) struct Watchdog {
+    pub action: WatchdogAction,
+    pub first: Vec<Option<(AnomalyKind, u64)>>,
+    pub stop_after: Option<u64>,
+}
+
+impl Watchdog {
+    /// Creates a watchdog that has not caught anything yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `action`: What to do when an anomaly is caught.
+    /// * `columns`: The number of registered columns.
+    ///
+    /// # Returns
+    ///
+    /// * `Watchdog`: The newly created watchdog.
+    pub fn new(action: WatchdogAction, columns: usize) -> Self {
+        Self {
+            action,
+            first: vec![None; columns],
+            stop_after: None,
+        }
+    }
+
+    /// Checks a value written to a column, and records it if it is the first anomaly in that column.
+    ///
+    /// # Arguments
+    ///
+    /// * `column`: The index of the column.
+    /// * `value`: The value, already converted to the type of the column.
+    /// * `sample`: The index of the current frame.
+    ///
+    /// # Returns
+    ///
+    /// * `bool`: Whether the value is an anomaly.
+    pub fn check(&mut self, column: usize, value: Value, sample: u64) -> bool {
+        let Some(kind) = AnomalyKind::of(value) else {
+            return false;
+        };
+
+        let first = &mut self.first[column];
+        if first.is_none() {
+            *first = Some((kind, sample));
+        }
+
+        if self.action == WatchdogAction::Stop && self.stop_after.is_none() {
+            self.stop_after = Some(sample);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_csv_as_audio_data, SampleLogger, Trigger};
+
+    #[test]
+    fn anomalies_are_classified() {
+        assert_eq!(
+            AnomalyKind::of(Value::F32(f32::NAN)),
+            Some(AnomalyKind::Nan)
+        );
+        assert_eq!(
+            AnomalyKind::of(Value::F64(f64::NEG_INFINITY)),
+            Some(AnomalyKind::Infinite)
+        );
+        assert_eq!(
+            AnomalyKind::of(Value::F32(f32::MIN_POSITIVE / 2.0)),
+            Some(AnomalyKind::Subnormal)
+        );
+        assert_eq!(AnomalyKind::of(Value::F64(f64::MIN_POSITIVE)), None);
+        assert_eq!(AnomalyKind::of(Value::F32(0.0)), None);
+        assert_eq!(AnomalyKind::of(Value::I64(0)), None);
+    }
+
+    /// Logs the columns `a` and `b` for 10 frames, where `a` is NaN in frame 2 and infinite in frame 4 and `b` is
+    /// subnormal in frame 3.
+    fn log(logger: &mut SampleLogger) {
+        let a = logger.column("a").unwrap();
+        let b = logger.column("b").unwrap();
+
+        for frame in 0..10 {
+            let value = match frame {
+                2 => f32::NAN,
+                4 => f32::INFINITY,
+                _ => frame as f32,
+            };
+            logger.write_column(a, value).unwrap();
+            let value = match frame {
+                3 => f32::MIN_POSITIVE / 2.0,
+                _ => 1.0,
+            };
+            logger.write_column(b, value).unwrap();
+            logger.end_frame().unwrap();
+        }
+    }
+
+    fn logger(filename: &str) -> (SampleLogger, String) {
+        let filename = std::env::temp_dir().join(filename);
+        let filename = filename.to_string_lossy().into_owned();
+        let mut logger = SampleLogger::new(filename.clone());
+        logger.register("a");
+        logger.register("b");
+
+        (logger, filename)
+    }
+
+    #[test]
+    fn first_anomaly_of_every_column_is_recorded() {
+        let (mut logger, filename) = logger("llad_watchdog.csv");
+        logger.set_watchdog(WatchdogAction::Record);
+        log(&mut logger);
+
+        let expected = vec![
+            Anomaly {
+                column: String::from("a"),
+                sample: 2,
+                kind: AnomalyKind::Nan,
+            },
+            Anomaly {
+                column: String::from("b"),
+                sample: 3,
+                kind: AnomalyKind::Subnormal,
+            },
+        ];
+        assert_eq!(logger.anomalies(), expected);
+
+        logger.write_debug_values().unwrap();
+        let data = read_csv_as_audio_data(filename).unwrap();
+        assert_eq!(data.metadata().anomalies, expected);
+        assert_eq!(data.get("a").unwrap().len(), 10);
+    }
+
+    #[test]
+    fn stop_ends_logging_after_the_anomaly() {
+        let (mut logger, _) = logger("llad_watchdog_stop.csv");
+        logger.set_watchdog(WatchdogAction::Stop);
+        log(&mut logger);
+
+        let a = logger.debug_values().get("a").unwrap().as_f32().unwrap();
+        assert_eq!(a.len(), 3);
+        assert!(a[2].is_nan());
+    }
+
+    #[test]
+    fn trigger_fires_in_the_frame_of_the_anomaly() {
+        let (mut logger, _) = logger("llad_watchdog_trigger.csv");
+        logger.set_trigger(Trigger::Manual, 1, 2).unwrap();
+        logger.set_watchdog(WatchdogAction::Trigger);
+        log(&mut logger);
+
+        let data = logger.captured().unwrap();
+        let a = data.get("a").unwrap().as_f32().unwrap();
+        assert_eq!(data.metadata().trigger_sample, Some(2));
+        assert_eq!(a.len(), 3);
+        assert_eq!((a[0], a[2]), (1.0, 3.0));
+        assert!(a[1].is_nan());
+    }
+}
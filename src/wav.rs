@@ -0,0 +1,177 @@
+use crate::{AudioData, LladError, SampleLogger};
+
+/// The contents of a WAV file.
+///
+/// # Fields
+///
+/// * `channels`: The samples of every channel, as floats in the range `[-1, 1]`.
+/// * `sample_rate`: The sample rate of the file in Hz.
+pub(crate) struct WavData {
+    pub channels: Vec<Vec<f32>>,
+    pub sample_rate: u32,
+}
+
+/// Reads a WAV file and converts every channel to floats in the range `[-1, 1]`.
+///
+/// # Arguments
+///
+/// * `filename`: The path to the WAV file.
+///
+/// # Returns
+///
+/// * `Result<WavData, LladError>`: The samples of every channel and the sample rate of the file, or the error that
+///   occurred while reading it.
+pub(crate) fn read_wav(filename: &str) -> Result<WavData, LladError> {
+    let mut reader = hound::WavReader::open(filename)?;
+    let spec = reader.spec();
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    let channel_count = spec.channels as usize;
+    let mut channels = vec![Vec::with_capacity(samples.len() / channel_count); channel_count];
+    for (i, sample) in samples.into_iter().enumerate() {
+        channels[i % channel_count].push(sample);
+    }
+
+    Ok(WavData {
+        channels,
+        sample_rate: spec.sample_rate,
+    })
+}
+
+/// Writes channels of floats to a 32-bit float WAV file.
+///
+/// # Arguments
+///
+/// * `filename`: The path to the WAV file.
+/// * `channels`: The samples of every channel. Channels that are shorter than the longest one are padded with silence.
+/// * `sample_rate`: The sample rate to store in the file.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the file is written, or the error that occurred while writing it.
+pub(crate) fn write_wav(
+    filename: &str,
+    channels: &[Vec<f32>],
+    sample_rate: u32,
+) -> Result<(), LladError> {
+    let spec = hound::WavSpec {
+        channels: channels.len() as u16,
+        sample_rate,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+
+    let mut writer = hound::WavWriter::create(filename, spec)?;
+    let length = channels.iter().map(Vec::len).max().unwrap_or(0);
+    for i in 0..length {
+        for channel in channels {
+            writer.write_sample(channel.get(i).copied().unwrap_or(0.0))?;
+        }
+    }
+
+    writer.finalize()?;
+    Ok(())
+}
+
+impl SampleLogger {
+    /// Writes logged columns as the channels of a WAV file, see [`write_columns_as_wav`], e.g. to listen to the
+    /// sidechain of a compressor. Call this on shutdown of the plugin, next to [`SampleLogger::write_debug_values`].
+    ///
+    /// # Arguments
+    ///
+    /// * `filename`: The name of the WAV file.
+    /// * `keys`: The keys of the columns to write, one channel per column in this order.
+    /// * `normalize`: Whether to scale every channel so that its peak is at full scale.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), LladError>`: Returns `Ok(())` if the file is written or if logging is disabled. Returns
+    ///   [`LladError::NotInMemory`] when streaming, or the errors of [`write_columns_as_wav`].
+    pub fn write_columns_as_wav(
+        &self,
+        filename: String,
+        keys: &[&str],
+        normalize: bool,
+    ) -> Result<(), LladError> {
+        if !cfg!(feature = "disabled") {
+            write_columns_as_wav(filename, &self.captured()?, keys, normalize)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes columns as the channels of a 32-bit float WAV file at the sample rate in their [`crate::Metadata`], so
+/// that an internal signal can be listened to. Columns of any type are converted as in [`crate::Value::to_f64`], NaN
+/// and infinite values are written as silence, and values beyond the range of an `f32` are clamped to it. Columns
+/// that are shorter than the longest one, such as the ones without a value in a frame that was still open when the
+/// logger was flushed, are padded with silence at the end.
+///
+/// Control signals such as envelopes rarely use the range `[-1, 1]` of audio. With `normalize`, every channel is
+/// scaled on its own so that its largest finite absolute value is at full scale. The peak and the scaling are computed
+/// in `f64`, so `f64` columns with values beyond the range of an `f32` are normalized correctly as well. Audio that
+/// should keep its level relative to full scale is best written to a separate file without normalization.
+///
+/// # Arguments
+///
+/// * `filename`: The name of the WAV file.
+/// * `data`: The columns, e.g. as read by [`crate::read_csv_as_audio_data`].
+/// * `keys`: The keys of the columns to write, one channel per column in this order.
+/// * `normalize`: Whether to scale every channel so that its peak is at full scale.
+///
+/// # Returns
+///
+/// * `Result<(), LladError>`: Returns `Ok(())` if the file is written. Returns [`LladError::UnknownColumn`] if a
+///   key is not in `data`, [`LladError::UnknownSampleRate`] if `data` has no sample rate, or the error while writing
+///   the file.
+pub fn write_columns_as_wav(
+    filename: String,
+    data: &AudioData,
+    keys: &[&str],
+    normalize: bool,
+) -> Result<(), LladError> {
+    let sample_rate = data
+        .metadata()
+        .sample_rate
+        .ok_or(LladError::UnknownSampleRate)?;
+
+    let mut channels = Vec::with_capacity(keys.len());
+    for &key in keys {
+        let values = data
+            .get(key)
+            .ok_or_else(|| LladError::UnknownColumn(String::from(key)))?;
+
+        let mut values = values.to_f64();
+        values
+            .iter_mut()
+            .filter(|value| !value.is_finite())
+            .for_each(|value| *value = 0.0);
+
+        let peak = values
+            .iter()
+            .fold(0.0_f64, |peak, value| peak.max(value.abs()));
+        if normalize && peak > 0.0 {
+            values.iter_mut().for_each(|value| *value /= peak);
+        }
+
+        let max = f64::from(f32::MAX);
+        channels.push(
+            values
+                .into_iter()
+                .map(|value| value.clamp(-max, max) as f32)
+                .collect(),
+        );
+    }
+
+    write_wav(filename.as_str(), &channels, sample_rate.round() as u32)
+}